@@ -0,0 +1,205 @@
+//Ethereum ABI codec
+pub type Selector = [u8; 4];
+pub type Word = [u8; 32];
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum AbiError {
+    /// call data is shorter than a function selector.
+    NoSelector,
+    /// unknown function selector {0:02x?}.
+    UnknownSelector(Selector),
+    /// call data is truncated at byte {0}.
+    Truncated(usize),
+    /// word at byte {0} is not a valid address.
+    InvalidAddress(usize),
+    /// word at byte {0} is not a valid boolean.
+    InvalidBool(usize),
+    /// value at byte {0} does not fit into the target integer type.
+    Overflow(usize),
+    /// array lengths do not match ({0} and {1}).
+    LengthMismatch(usize, usize),
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Token {
+    Word(Word),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<Token>),
+}
+
+impl Token {
+    pub fn address(account: &Account) -> Self {
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(&account.to_byte_array());
+        Token::Word(word)
+    }
+
+    pub fn u256(value: U256) -> Self { Token::Word(value.to_be_bytes()) }
+
+    pub fn u64(value: u64) -> Self {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        Token::Word(word)
+    }
+
+    pub fn bool(value: bool) -> Self { Self::u64(value as u64) }
+
+    fn is_dynamic(&self) -> bool { !matches!(self, Token::Word(_)) }
+
+    fn encode_tail(&self) -> Vec<u8> {
+        match self {
+            Token::Word(word) => word.to_vec(),
+            Token::Bytes(data) => encode_bytes(data),
+            Token::String(s) => encode_bytes(s.as_bytes()),
+            Token::Array(items) => {
+                let mut out = Token::u64(items.len() as u64).encode_tail();
+                out.extend(encode_tokens(items));
+                out
+            }
+        }
+    }
+}
+
+fn encode_bytes(data: &[u8]) -> Vec<u8> {
+    let mut out = Token::u64(data.len() as u64).encode_tail();
+    out.extend_from_slice(data);
+    out.resize(out.len() + (32 - data.len() % 32) % 32, 0);
+    out
+}
+
+pub fn encode_tokens(tokens: &[Token]) -> Vec<u8> {
+    let head_len = tokens.len() * 32;
+    let mut head = Vec::with_capacity(head_len);
+    let mut tail = Vec::new();
+    for token in tokens {
+        if token.is_dynamic() {
+            head.extend(Token::u64((head_len + tail.len()) as u64).encode_tail());
+            tail.extend(token.encode_tail());
+        } else {
+            head.extend(token.encode_tail());
+        }
+    }
+    head.extend(tail);
+    head
+}
+
+pub fn encode_call(selector: Selector, tokens: &[Token]) -> Vec<u8> {
+    let mut out = selector.to_vec();
+    out.extend(encode_tokens(tokens));
+    out
+}
+
+pub fn split_selector(data: &[u8]) -> Result<(Selector, AbiReader), AbiError> {
+    if data.len() < 4 {
+        return Err(AbiError::NoSelector);
+    }
+    let mut selector = [0u8; 4];
+    selector.copy_from_slice(&data[..4]);
+    Ok((selector, AbiReader::new(&data[4..])))
+}
+
+pub struct AbiReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> AbiReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self { AbiReader { data, pos: 0 } }
+
+    fn word_at(&self, pos: usize) -> Result<Word, AbiError> {
+        let mut word = [0u8; 32];
+        word.copy_from_slice(self.data.get(pos..pos + 32).ok_or(AbiError::Truncated(pos))?);
+        Ok(word)
+    }
+
+    fn usize_at(&self, pos: usize) -> Result<usize, AbiError> {
+        let word = self.word_at(pos)?;
+        if word[..24].iter().any(|b| *b != 0) {
+            return Err(AbiError::Overflow(pos));
+        }
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(&word[24..]);
+        usize::try_from(u64::from_be_bytes(buf)).map_err(|_| AbiError::Overflow(pos))
+    }
+
+    fn next_pos(&mut self) -> usize {
+        let pos = self.pos;
+        self.pos += 32;
+        pos
+    }
+
+    pub fn word(&mut self) -> Result<Word, AbiError> {
+        let pos = self.next_pos();
+        self.word_at(pos)
+    }
+
+    pub fn u256(&mut self) -> Result<U256, AbiError> { self.word().map(U256::from_be_bytes) }
+
+    pub fn u64(&mut self) -> Result<u64, AbiError> {
+        let pos = self.next_pos();
+        self.usize_at(pos).map(|v| v as u64)
+    }
+
+    pub fn bool(&mut self) -> Result<bool, AbiError> {
+        let pos = self.next_pos();
+        match self.usize_at(pos) {
+            Ok(0) => Ok(false),
+            Ok(1) => Ok(true),
+            _ => Err(AbiError::InvalidBool(pos)),
+        }
+    }
+
+    pub fn bytes4(&mut self) -> Result<[u8; 4], AbiError> {
+        let pos = self.next_pos();
+        let word = self.word_at(pos)?;
+        if word[4..].iter().any(|b| *b != 0) {
+            return Err(AbiError::Overflow(pos));
+        }
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&word[..4]);
+        Ok(bytes)
+    }
+
+    pub fn address(&mut self) -> Result<Account, AbiError> {
+        let pos = self.next_pos();
+        let word = self.word_at(pos)?;
+        if word[..12].iter().any(|b| *b != 0) {
+            return Err(AbiError::InvalidAddress(pos));
+        }
+        let mut addr = [0u8; 20];
+        addr.copy_from_slice(&word[12..]);
+        Ok(Account::from(addr))
+    }
+
+    fn dynamic(&mut self) -> Result<AbiReader<'a>, AbiError> {
+        let pos = self.next_pos();
+        let offset = self.usize_at(pos)?;
+        let data = self.data.get(offset..).ok_or(AbiError::Truncated(offset))?;
+        Ok(AbiReader::new(data))
+    }
+
+    pub fn bytes(&mut self) -> Result<Vec<u8>, AbiError> {
+        let pos = self.pos;
+        let inner = self.dynamic()?;
+        let len = inner.usize_at(0)?;
+        let end = len.checked_add(32).ok_or(AbiError::Overflow(pos))?;
+        inner.data.get(32..end).map(<[u8]>::to_vec).ok_or(AbiError::Truncated(end))
+    }
+
+    pub fn string(&mut self) -> Result<String, AbiError> {
+        let pos = self.pos;
+        String::from_utf8(self.bytes()?).map_err(|_| AbiError::Truncated(pos))
+    }
+
+    pub fn array<T>(
+        &mut self,
+        mut item: impl FnMut(&mut AbiReader<'a>) -> Result<T, AbiError>,
+    ) -> Result<Vec<T>, AbiError> {
+        let mut inner = self.dynamic()?;
+        let len = inner.u64()? as usize;
+        let mut elems = AbiReader::new(inner.data.get(32..).ok_or(AbiError::Truncated(32))?);
+        (0..len).map(|_| item(&mut elems)).collect()
+    }
+}
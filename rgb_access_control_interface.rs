@@ -0,0 +1,164 @@
+//RGB-AccessControl
+use bp::bc::stl::bitcoin_stl;
+use strict_types::stl::std_stl;
+use strict_types::{CompileError, LibBuilder, TypeLib};
+
+use super::{AssignIface, GenesisIface, Iface, OwnedIface, Req, TransitionIface, VerNo};
+use crate::interface::ArgSpec;
+use crate::stl::{rgb_contract_stl, StandardTypes};
+
+pub const LIB_NAME_RGB_ACCESS_CONTROL: &str = "RGB-AccessControl";
+
+const MISSING_ROLE: u8 = 1;
+const NOT_ADMIN: u8 = 2;
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_ACCESS_CONTROL, tags = repr, into_u8, try_from_u8)]
+#[repr(u8)]
+pub enum Error {
+    #[strict_type(dumb)]
+    MissingRole = MISSING_ROLE,
+    NotAdmin = NOT_ADMIN,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_ACCESS_CONTROL, tags = repr, into_u8, try_from_u8)]
+#[repr(u8)]
+pub enum Role {
+    #[strict_type(dumb)]
+    Admin = 0,
+    Minter = 1,
+    Burner = 2,
+    Pauser = 3,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::Admin, Role::Minter, Role::Burner, Role::Pauser];
+
+    /// Name of the interface assignment holding the role right.
+    pub fn assignment(self) -> &'static str {
+        match self {
+            Role::Admin => "admin_role",
+            Role::Minter => "minter_role",
+            Role::Burner => "burner_role",
+            Role::Pauser => "pauser_role",
+        }
+    }
+}
+
+fn _rgb_access_control_stl() -> Result<TypeLib, CompileError> {
+    LibBuilder::new(libname!(LIB_NAME_RGB_ACCESS_CONTROL), tiny_bset! {
+        std_stl().to_dependency(),
+        bitcoin_stl().to_dependency(),
+        rgb_contract_stl().to_dependency()
+    })
+    .transpile::<Role>()
+    .transpile::<Error>()
+    .compile()
+}
+
+pub fn rgb_access_control_stl() -> TypeLib {
+    _rgb_access_control_stl().expect("invalid strict type RGB-AccessControl library")
+}
+
+/// Role rights are single-use seals owned by the role holders. Revoking a
+/// role therefore spends both the admin right and the revoked role right,
+/// so the holder has to co-sign the witness; holders may also renounce a
+/// role on their own.
+pub fn rgb_access_control() -> Iface {
+    let types = StandardTypes::with(rgb_access_control_stl());
+
+    Iface {
+        version: VerNo::V1,
+        name: tn!("RGB-AccessControl"),
+        global_state: none!(),
+        assignments: tiny_bmap! {
+            fname!("admin_role") => AssignIface::private(OwnedIface::Rights, Req::OneOrMore),
+            fname!("minter_role") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
+            fname!("burner_role") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
+            fname!("pauser_role") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
+        },
+        genesis: GenesisIface {
+            metadata: None,
+            global: none!(),
+            assignments: tiny_bmap! {
+                fname!("admin_role") => ArgSpec::required(),
+                fname!("minter_role") => ArgSpec::many(),
+                fname!("burner_role") => ArgSpec::many(),
+                fname!("pauser_role") => ArgSpec::many(),
+            },
+            valencies: none!(),
+            errors: none!(),
+        },
+        transitions: tiny_bmap! {
+            tn!("GrantRole") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("admin_role"),
+                    fname!("role") => ArgSpec::from_required("Role"),
+                    fname!("account") => ArgSpec::from_required("Account"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    NOT_ADMIN
+                },
+                default_assignment: Some(fname!("admin_role")),
+            },
+            tn!("RevokeRole") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("admin_role"),
+                    fname!("role") => ArgSpec::from_required("Role"),
+                    fname!("account") => ArgSpec::from_required("Account"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    NOT_ADMIN,
+                    MISSING_ROLE
+                },
+                default_assignment: Some(fname!("admin_role")),
+            },
+            tn!("RenounceRole") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("role") => ArgSpec::from_required("Role"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    MISSING_ROLE
+                },
+                default_assignment: None,
+            },
+            tn!("HasRole") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("role") => ArgSpec::from_required("Role"),
+                    fname!("account") => ArgSpec::from_required("Account"),
+                },
+                valencies: none!(),
+                errors: none!(),
+                default_assignment: None,
+            },
+        },
+        extensions: none!(),
+        error_type: types.get("RGB-AccessControl.Error"),
+        default_operation: None,
+    }
+}
+
+pub trait RgbAccessControlIface {
+    fn grant_role(role: Role, account: Account);
+    fn revoke_role(role: Role, account: Account);
+    fn renounce_role(role: Role);
+    fn has_role(role: Role, account: Account) -> bool;
+}
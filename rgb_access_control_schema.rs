@@ -0,0 +1,115 @@
+///RGB-AccessControl SCHEMA
+use aluvm::library::Lib;
+use rgbstd::interface::rgb_access_control::Error;
+use rgbstd::interface::{rgb_access_control, IfaceImpl, NamedField, NamedType, VerNo};
+use rgbstd::schema::{Occurrences, SchemaId, StateSchema, TransitionSchema};
+use strict_types::{SemId, Ty};
+
+use crate::rgb_alu::{all_of, any_inputs, spends_more};
+
+pub const OS_ROLE_ADMIN: u16 = 30000;
+pub const OS_ROLE_MINTER: u16 = 30001;
+pub const OS_ROLE_BURNER: u16 = 30002;
+pub const OS_ROLE_PAUSER: u16 = 30003;
+pub const TS_GRANT_ROLE: u16 = 30000;
+pub const TS_REVOKE_ROLE: u16 = 30001;
+pub const TS_RENOUNCE_ROLE: u16 = 30002;
+pub const TS_HAS_ROLE: u16 = 30003;
+
+pub const ROLE_RIGHT: StateSchema = StateSchema::Declarative;
+
+pub fn grant_role_schema() -> TransitionSchema {
+    TransitionSchema {
+        metadata: Ty::<SemId>::UNIT.id(None),
+        globals: none!(),
+        inputs: tiny_bmap! {
+            OS_ROLE_ADMIN => Occurrences::Once
+        },
+        assignments: tiny_bmap! {
+            OS_ROLE_ADMIN => Occurrences::OnceOrMore,
+            OS_ROLE_MINTER => Occurrences::NoneOrMore,
+            OS_ROLE_BURNER => Occurrences::NoneOrMore,
+            OS_ROLE_PAUSER => Occurrences::NoneOrMore
+        },
+        valencies: none!(),
+    }
+}
+
+pub fn revoke_role_schema() -> TransitionSchema {
+    TransitionSchema {
+        metadata: Ty::<SemId>::UNIT.id(None),
+        globals: none!(),
+        inputs: tiny_bmap! {
+            OS_ROLE_ADMIN => Occurrences::OnceOrMore,
+            OS_ROLE_MINTER => Occurrences::NoneOrMore,
+            OS_ROLE_BURNER => Occurrences::NoneOrMore,
+            OS_ROLE_PAUSER => Occurrences::NoneOrMore
+        },
+        assignments: tiny_bmap! {
+            OS_ROLE_ADMIN => Occurrences::Once
+        },
+        valencies: none!(),
+    }
+}
+
+/// Revocation is authorized by an admin right and must drop at least one
+/// role right; the admin right itself is reassigned.
+pub fn revoke_role_lib() -> Lib {
+    all_of([
+        any_inputs(&[OS_ROLE_ADMIN], Error::NotAdmin as u8),
+        spends_more(
+            &[OS_ROLE_ADMIN, OS_ROLE_MINTER, OS_ROLE_BURNER, OS_ROLE_PAUSER],
+            Error::MissingRole as u8,
+        ),
+    ])
+}
+
+pub fn renounce_role_schema() -> TransitionSchema {
+    TransitionSchema {
+        metadata: Ty::<SemId>::UNIT.id(None),
+        globals: none!(),
+        inputs: tiny_bmap! {
+            OS_ROLE_ADMIN => Occurrences::NoneOrOnce,
+            OS_ROLE_MINTER => Occurrences::NoneOrOnce,
+            OS_ROLE_BURNER => Occurrences::NoneOrOnce,
+            OS_ROLE_PAUSER => Occurrences::NoneOrOnce
+        },
+        assignments: none!(),
+        valencies: none!(),
+    }
+}
+
+pub fn has_role_schema() -> TransitionSchema {
+    TransitionSchema {
+        metadata: Ty::<SemId>::UNIT.id(None),
+        globals: none!(),
+        inputs: none!(),
+        assignments: none!(),
+        valencies: none!(),
+    }
+}
+
+pub fn access_control_impl(schema_id: SchemaId) -> IfaceImpl {
+    let iface = rgb_access_control();
+
+    IfaceImpl {
+        version: VerNo::V1,
+        schema_id,
+        iface_id: iface.iface_id(),
+        global_state: none!(),
+        assignments: tiny_bset! {
+            NamedField::with(OS_ROLE_ADMIN, fname!("admin_role")),
+            NamedField::with(OS_ROLE_MINTER, fname!("minter_role")),
+            NamedField::with(OS_ROLE_BURNER, fname!("burner_role")),
+            NamedField::with(OS_ROLE_PAUSER, fname!("pauser_role")),
+        },
+        valencies: none!(),
+        transitions: tiny_bset! {
+            NamedType::with(TS_GRANT_ROLE, tn!("grant_role")),
+            NamedType::with(TS_REVOKE_ROLE, tn!("revoke_role")),
+            NamedType::with(TS_RENOUNCE_ROLE, tn!("renounce_role")),
+            NamedType::with(TS_HAS_ROLE, tn!("has_role")),
+        },
+        extensions: none!(),
+    }
+}
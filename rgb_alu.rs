@@ -0,0 +1,623 @@
+//AluVM checks shared by RGB-E* schemas
+use aluvm::data::ByteStr;
+use aluvm::isa::{
+    ArithmeticOp, BitwiseOp, BytesOp, CmpOp, ControlFlowOp, Instr, IntFlags, MovOp, PutOp,
+    SignFlag,
+};
+use aluvm::library::Lib;
+use aluvm::reg::{Reg16, Reg32, RegA, RegR, RegS};
+use rgbstd::vm::{ContractOp, RgbIsa};
+
+pub type Check = Vec<Instr<RgbIsa>>;
+
+/// Maximum number of states of a type summed by [`conserves_u256`]. AluVM
+/// addresses operation state by immediate index, so the sum is unrolled.
+pub const MAX_U256_STATES: u16 = 16;
+
+/// Sets the error code reported by the validator if the following check
+/// fails.
+pub fn errno(code: u8) -> Instr<RgbIsa> {
+    Instr::Put(PutOp::PutA(RegA::A8, Reg32::Reg0, Box::new(code.into())))
+}
+
+pub(crate) fn code_len(code: &[Instr<RgbIsa>]) -> u16 {
+    if code.is_empty() {
+        return 0;
+    }
+    Lib::assemble(code).expect("invalid AluVM code").code.len() as u16
+}
+
+/// Checks are written as if they start at offset 0; moves their jump
+/// targets to where the check is placed in the library.
+fn relocate(check: Check, base: u16) -> Check {
+    check
+        .into_iter()
+        .map(|instr| match instr {
+            Instr::ControlFlow(ControlFlowOp::Jmp(pos)) => {
+                Instr::ControlFlow(ControlFlowOp::Jmp(pos + base))
+            }
+            Instr::ControlFlow(ControlFlowOp::Jif(pos)) => {
+                Instr::ControlFlow(ControlFlowOp::Jif(pos + base))
+            }
+            instr => instr,
+        })
+        .collect()
+}
+
+/// Assembles checks into a single library. Each check must leave its result
+/// in `st0`; execution stops at the first failed check.
+pub fn all_of(checks: impl IntoIterator<Item = Check>) -> Lib {
+    let guard = [
+        Instr::ControlFlow(ControlFlowOp::Jif(0)),
+        Instr::ControlFlow(ControlFlowOp::Fail),
+    ];
+    let mut code = vec![];
+    let mut checks = checks.into_iter().peekable();
+    while let Some(check) = checks.next() {
+        let base = code_len(&code);
+        code.extend(relocate(check, base));
+        if checks.peek().is_some() {
+            let next = code_len(&code) + code_len(&guard);
+            code.push(Instr::ControlFlow(ControlFlowOp::Jif(next)));
+            code.push(Instr::ControlFlow(ControlFlowOp::Fail));
+        }
+    }
+    Lib::assemble(&code).expect("invalid AluVM code")
+}
+
+/// Always fails, for operations a schema variant does not support.
+pub fn unsupported(code: u8) -> Check { vec![errno(code), Instr::ControlFlow(ControlFlowOp::Fail)] }
+
+/// Succeeds if the operation spends no state of the given type.
+pub fn no_inputs(ty: u16, code: u8) -> Check {
+    vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnP(ty, Reg16::Reg0))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg1, Box::new(0u16.into()))),
+        Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg1)),
+    ]
+}
+
+/// Succeeds if the operation spends at least one state of any of the given
+/// types, e.g. either a role right or the owner right.
+pub fn any_inputs(types: &[u16], code: u8) -> Check {
+    let mut check = vec![
+        errno(code),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg0, Box::new(0u16.into()))),
+    ];
+    for ty in types {
+        check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnP(*ty, Reg16::Reg1))));
+        check.push(Instr::Arithmetic(ArithmeticOp::Add(none!(), RegA::A16, Reg32::Reg1, Reg32::Reg0)));
+    }
+    check.push(Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg2, Box::new(0u16.into()))));
+    check.push(Instr::Cmp(CmpOp::GtA(SignFlag::Unsigned, RegA::A16, Reg32::Reg0, Reg32::Reg2)));
+    check
+}
+
+/// Succeeds if the operation defines as many `global` values as it assigns
+/// states of the `owned` types, e.g. one `TokenData` per minted allocation.
+pub fn counts_match(global: u16, owned: &[u16], code: u8) -> Check {
+    let mut check = vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnG(global, Reg16::Reg0))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg1, Box::new(0u16.into()))),
+    ];
+    for ty in owned {
+        check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnS(*ty, Reg16::Reg2))));
+        check.push(Instr::Arithmetic(ArithmeticOp::Add(none!(), RegA::A16, Reg32::Reg2, Reg32::Reg1)));
+    }
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg1)));
+    check
+}
+
+/// Succeeds if the operation defines as many `global` values as it spends
+/// states of the given types, e.g. one burned token id per allocation.
+pub fn spent_count_matches(global: u16, inputs: &[u16], code: u8) -> Check {
+    let mut check = vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnG(global, Reg16::Reg0))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg1, Box::new(0u16.into()))),
+    ];
+    for ty in inputs {
+        check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnP(*ty, Reg16::Reg2))));
+        check.push(Instr::Arithmetic(ArithmeticOp::Add(none!(), RegA::A16, Reg32::Reg2, Reg32::Reg1)));
+    }
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg1)));
+    check
+}
+
+/// Runs `body(index, offset)` for each index below `a16[count]`, up to
+/// `max`; `offset` is where the body is placed, for its own jumps. AluVM
+/// addresses state by immediate index, so the loop is unrolled; execution
+/// stops if a body leaves `st0` unset.
+fn unrolled(check: &mut Check, count: Reg32, max: u16, body: impl Fn(u16, u16) -> Check) {
+    for index in 0..max {
+        check.push(Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg2, Box::new(index.into()))));
+        check.push(Instr::Cmp(CmpOp::LtA(SignFlag::Unsigned, RegA::A16, Reg32::Reg2, count)));
+        let start = code_len(check) + code_len(&[Instr::ControlFlow(ControlFlowOp::Jif(0))]) * 2;
+        let body = body(index, start);
+        let guard = code_len(&body) + code_len(&[Instr::ControlFlow(ControlFlowOp::Jif(0))]);
+        let next = start + guard + code_len(&[Instr::ControlFlow(ControlFlowOp::Fail)]);
+        check.push(Instr::ControlFlow(ControlFlowOp::Jif(start)));
+        check.push(Instr::ControlFlow(ControlFlowOp::Jmp(next)));
+        check.extend(body);
+        check.push(Instr::ControlFlow(ControlFlowOp::Jif(next)));
+        check.push(Instr::ControlFlow(ControlFlowOp::Fail));
+    }
+}
+
+/// Fails unless `st0` is set, continuing with the instruction following the
+/// guard otherwise.
+fn guard(check: &mut Check) {
+    let next = code_len(check)
+        + code_len(&[Instr::ControlFlow(ControlFlowOp::Jif(0))])
+        + code_len(&[Instr::ControlFlow(ControlFlowOp::Fail)]);
+    check.push(Instr::ControlFlow(ControlFlowOp::Jif(next)));
+    check.push(Instr::ControlFlow(ControlFlowOp::Fail));
+}
+
+/// Fails if `a16[count]` exceeds `max`, instead of ignoring the excess states.
+fn at_most(check: &mut Check, count: Reg32, max: u16) {
+    check.push(Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg4, Box::new((max + 1).into()))));
+    check.push(Instr::Cmp(CmpOp::LtA(SignFlag::Unsigned, RegA::A16, count, Reg32::Reg4)));
+    guard(check);
+}
+
+fn load(ty: u16, inputs: bool, index: u16) -> Instr<RgbIsa> {
+    let op = if inputs {
+        ContractOp::LdP(ty, index, RegS::from(0))
+    } else {
+        ContractOp::LdS(ty, index, RegS::from(0))
+    };
+    Instr::ExtensionCodes(RgbIsa::Contract(op))
+}
+
+/// Adds up to `max` 256-bit amounts of type `ty`, read at byte `a16[offset]`
+/// of each state, into `a256[acc]`; `a16[count]` must hold the number of
+/// states. Inputs are read from the spent state, outputs from the assigned
+/// one. Fails on overflow.
+#[allow(clippy::too_many_arguments)]
+fn sum_u256(
+    check: &mut Check,
+    ty: u16,
+    inputs: bool,
+    count: Reg32,
+    max: u16,
+    offset: Reg16,
+    acc: Reg32,
+) {
+    let checked = IntFlags { signed: false, wrap: false };
+    unrolled(check, count, max, |index, _| {
+        vec![
+            load(ty, inputs, index),
+            Instr::Bytes(BytesOp::Extr(RegS::from(0), RegR::R256, Reg16::Reg0, offset)),
+            Instr::Move(MovOp::SpyAR(RegA::A256, Reg32::Reg0, RegR::R256, Reg32::Reg0)),
+            Instr::Arithmetic(ArithmeticOp::AddA(checked, RegA::A256, Reg32::Reg0, acc)),
+        ]
+    });
+}
+
+/// Succeeds if the operation assigns back exactly the states of type `ty` it
+/// spends, in the same order and unchanged, so it can authenticate with an
+/// allocation without moving it. Handles up to [`MAX_U256_STATES`] states.
+pub fn reassigns_unchanged(ty: u16, code: u8) -> Check {
+    let mut check = vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnP(ty, Reg16::Reg0))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnS(ty, Reg16::Reg1))),
+        Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg1)),
+    ];
+    guard(&mut check);
+    at_most(&mut check, Reg32::Reg0, MAX_U256_STATES);
+    unrolled(&mut check, Reg32::Reg0, MAX_U256_STATES, |index, _| {
+        vec![
+            Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdP(ty, index, RegS::from(0)))),
+            Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdS(ty, index, RegS::from(1)))),
+            Instr::Bytes(BytesOp::Eq(RegS::from(0), RegS::from(1))),
+        ]
+    });
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg1)));
+    check
+}
+
+/// Succeeds if the Pedersen commitments to the amounts of type `ty` spent by
+/// the operation balance the ones it assigns, as verified by `PcVs`. Unlike
+/// [`conserves_u256`], it can't tell an issued or burned excess from an
+/// unbalanced transfer, so it only fits transfers.
+pub fn conserves_committed(ty: u16, code: u8) -> Check {
+    vec![errno(code), Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::PcVs(ty)))]
+}
+
+/// Succeeds if the 256-bit amounts of type `ty` spent by the operation sum
+/// to the amounts it assigns, without overflowing on either side. Takes the
+/// place of `PcVs` for state which does not fit the 64-bit fungible type.
+pub fn conserves_u256(ty: u16, code: u8) -> Check {
+    let mut check = sums_u256(ty, code);
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A256, Reg32::Reg1, Reg32::Reg2)));
+    check
+}
+
+/// Succeeds if the amounts of type `ty` assigned by the operation sum to at
+/// most the spent ones, e.g. a burn. Handles up to [`MAX_U256_STATES`]
+/// states on each side.
+pub fn reduces_u256(ty: u16, code: u8) -> Check {
+    let mut check = sums_u256(ty, code);
+    check.push(Instr::Cmp(CmpOp::GtA(SignFlag::Unsigned, RegA::A256, Reg32::Reg2, Reg32::Reg1)));
+    check.push(Instr::Cmp(CmpOp::StInv));
+    check
+}
+
+/// Sums the spent amounts of type `ty` into `a256[1]` and the assigned ones
+/// into `a256[2]`.
+fn sums_u256(ty: u16, code: u8) -> Check {
+    let mut check = vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnP(ty, Reg16::Reg0))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnS(ty, Reg16::Reg1))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg3, Box::new(0u16.into()))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg4, Box::new(MAX_U256_STATES.into()))),
+    ];
+    // more states than can be summed: fail instead of ignoring the excess
+    let over = |count| Instr::Cmp(CmpOp::GtA(SignFlag::Unsigned, RegA::A16, count, Reg32::Reg4));
+    check.push(over(Reg32::Reg0));
+    let jif_inputs = check.len();
+    check.push(Instr::ControlFlow(ControlFlowOp::Jif(0)));
+    check.push(over(Reg32::Reg1));
+    let jif_outputs = check.len();
+    check.push(Instr::ControlFlow(ControlFlowOp::Jif(0)));
+    let jmp_proceed = check.len();
+    check.push(Instr::ControlFlow(ControlFlowOp::Jmp(0)));
+    let fail = code_len(&check);
+    check.push(Instr::ControlFlow(ControlFlowOp::Fail));
+    let proceed = code_len(&check);
+    check[jif_inputs] = Instr::ControlFlow(ControlFlowOp::Jif(fail));
+    check[jif_outputs] = Instr::ControlFlow(ControlFlowOp::Jif(fail));
+    check[jmp_proceed] = Instr::ControlFlow(ControlFlowOp::Jmp(proceed));
+    check.push(Instr::Put(PutOp::PutA(RegA::A256, Reg32::Reg1, Box::new(0u8.into()))));
+    check.push(Instr::Put(PutOp::PutA(RegA::A256, Reg32::Reg2, Box::new(0u8.into()))));
+    sum_u256(&mut check, ty, true, Reg32::Reg0, MAX_U256_STATES, Reg16::Reg3, Reg32::Reg1);
+    sum_u256(&mut check, ty, false, Reg32::Reg1, MAX_U256_STATES, Reg16::Reg3, Reg32::Reg2);
+    check
+}
+
+/// Maximum number of states of a type checked by [`conserves_u256_by_id`],
+/// which compares every input against every state and so grows
+/// quadratically.
+pub const MAX_ID_STATES: u16 = 8;
+
+/// Adds to `a256[acc]` the amounts of the states of type `ty` whose token id
+/// equals `r256[1]`. States are a 256-bit id followed by a 256-bit amount.
+fn sum_u256_of_id(check: &mut Check, ty: u16, inputs: bool, count: Reg32, acc: Reg32) {
+    let checked = IntFlags { signed: false, wrap: false };
+    unrolled(check, count, MAX_ID_STATES, |index, offset| {
+        let compare = vec![
+            load(ty, inputs, index),
+            Instr::Bytes(BytesOp::Extr(RegS::from(0), RegR::R256, Reg16::Reg2, Reg16::Reg3)),
+            Instr::Cmp(CmpOp::EqR(none!(), RegR::R256, Reg32::Reg1, Reg32::Reg2)),
+        ];
+        // other ids leave the sum as is, with `st0` set for the loop guard
+        let skip = vec![Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg3, Reg32::Reg3))];
+        let add = vec![
+            Instr::Bytes(BytesOp::Extr(RegS::from(0), RegR::R256, Reg16::Reg0, Reg16::Reg5)),
+            Instr::Move(MovOp::SpyAR(RegA::A256, Reg32::Reg0, RegR::R256, Reg32::Reg0)),
+            Instr::Arithmetic(ArithmeticOp::AddA(checked, RegA::A256, Reg32::Reg0, acc)),
+        ];
+        let jump = code_len(&[Instr::ControlFlow(ControlFlowOp::Jif(0))]);
+        let at_add = offset + code_len(&compare) + jump + code_len(&skip) + jump;
+        let at_end = at_add + code_len(&add);
+        let mut body = compare;
+        body.push(Instr::ControlFlow(ControlFlowOp::Jif(at_add)));
+        body.extend(skip);
+        body.push(Instr::ControlFlow(ControlFlowOp::Jmp(at_end)));
+        body.extend(add);
+        body
+    });
+}
+
+/// Succeeds if, for every token id spent by the operation, the amounts of
+/// that id it spends sum to the amounts of that id it assigns, and if the
+/// total amounts match, so no amount can be assigned to an id which is not
+/// spent. States of type `ty` are a 256-bit token id followed by a 256-bit
+/// amount, e.g. RGB-E1155 balances. Handles up to [`MAX_ID_STATES`] states.
+pub fn conserves_u256_by_id(ty: u16, code: u8) -> Check {
+    let mut check = vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnP(ty, Reg16::Reg0))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnS(ty, Reg16::Reg1))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg3, Box::new(0u16.into()))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg5, Box::new(32u16.into()))),
+    ];
+    at_most(&mut check, Reg32::Reg0, MAX_ID_STATES);
+    at_most(&mut check, Reg32::Reg1, MAX_ID_STATES);
+    unrolled(&mut check, Reg32::Reg0, MAX_ID_STATES, |index, offset| {
+        let mut body = vec![
+            Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdP(ty, index, RegS::from(0)))),
+            Instr::Bytes(BytesOp::Extr(RegS::from(0), RegR::R256, Reg16::Reg1, Reg16::Reg3)),
+            Instr::Put(PutOp::PutA(RegA::A256, Reg32::Reg1, Box::new(0u8.into()))),
+            Instr::Put(PutOp::PutA(RegA::A256, Reg32::Reg2, Box::new(0u8.into()))),
+        ];
+        // the inner sums place their jumps relative to the outer body
+        let mut inner = vec![];
+        sum_u256_of_id(&mut inner, ty, true, Reg32::Reg0, Reg32::Reg1);
+        sum_u256_of_id(&mut inner, ty, false, Reg32::Reg1, Reg32::Reg2);
+        body.extend(relocate(inner, offset + code_len(&body)));
+        body.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A256, Reg32::Reg1, Reg32::Reg2)));
+        body
+    });
+    check.push(Instr::Put(PutOp::PutA(RegA::A256, Reg32::Reg1, Box::new(0u8.into()))));
+    check.push(Instr::Put(PutOp::PutA(RegA::A256, Reg32::Reg2, Box::new(0u8.into()))));
+    sum_u256(&mut check, ty, true, Reg32::Reg0, MAX_ID_STATES, Reg16::Reg5, Reg32::Reg1);
+    sum_u256(&mut check, ty, false, Reg32::Reg1, MAX_ID_STATES, Reg16::Reg5, Reg32::Reg2);
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A256, Reg32::Reg1, Reg32::Reg2)));
+    check
+}
+
+/// Succeeds if the operation assigns as many states of type `ty` as it
+/// spends, e.g. an operator right used for a transfer is kept.
+pub fn keeps_count(ty: u16, code: u8) -> Check {
+    vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnP(ty, Reg16::Reg0))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnS(ty, Reg16::Reg1))),
+        Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg1)),
+    ]
+}
+
+/// Succeeds if the operation spends more states of the given types than it
+/// assigns, e.g. a revocation drops at least one role right.
+pub fn spends_more(types: &[u16], code: u8) -> Check {
+    let mut check = vec![
+        errno(code),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg0, Box::new(0u16.into()))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg1, Box::new(0u16.into()))),
+    ];
+    for ty in types {
+        check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnP(*ty, Reg16::Reg2))));
+        check.push(Instr::Arithmetic(ArithmeticOp::Add(none!(), RegA::A16, Reg32::Reg2, Reg32::Reg0)));
+        check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnS(*ty, Reg16::Reg2))));
+        check.push(Instr::Arithmetic(ArithmeticOp::Add(none!(), RegA::A16, Reg32::Reg2, Reg32::Reg1)));
+    }
+    check.push(Instr::Cmp(CmpOp::GtA(SignFlag::Unsigned, RegA::A16, Reg32::Reg0, Reg32::Reg1)));
+    check
+}
+
+/// Loads the counter leading the value in `s[src]` into `a128[dst]`;
+/// `a128[3]` must hold the mask of its width and `a16[3]` zero. The value is
+/// padded with zeros first, so it may be shorter than 16 bytes, e.g. an
+/// `Amount`.
+fn load_counter(check: &mut Check, src: u8, dst: Reg32) {
+    let reg = match dst {
+        Reg32::Reg0 => Reg16::Reg0,
+        _ => Reg16::Reg1,
+    };
+    let pad = ByteStr::with([0u8; 16]);
+    check.push(Instr::Bytes(BytesOp::Put(RegS::from(15), Box::new(pad), false)));
+    check.push(Instr::Bytes(BytesOp::Join(RegS::from(src), RegS::from(15), RegS::from(src))));
+    check.push(Instr::Bytes(BytesOp::Extr(RegS::from(src), RegR::R128, reg, Reg16::Reg3)));
+    check.push(Instr::Move(MovOp::SpyAR(RegA::A128, dst, RegR::R128, dst)));
+    check.push(Instr::Bitwise(BitwiseOp::And(RegA::A128.into(), dst, Reg32::Reg3, dst)));
+}
+
+/// Succeeds if the `u64` counter leading the state of type `ty` assigned by
+/// the operation is greater than the one of the state of that type it
+/// spends, or if it spends none, which starts a new sequence. Used for the
+/// permit nonces each owner keeps on their own seal, so a signed permit
+/// can't be replayed.
+pub fn counter_advances(ty: u16, code: u8) -> Check {
+    let mut check = vec![
+        errno(code),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg3, Box::new(0u16.into()))),
+        Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg3, Box::new(u64::MAX.into()))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnP(ty, Reg16::Reg1))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg4, Box::new(0u16.into()))),
+        Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg1, Reg32::Reg4)),
+    ];
+    let jif_first = check.len();
+    check.push(Instr::ControlFlow(ControlFlowOp::Jif(0)));
+    check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdS(ty, 0, RegS::from(0)))));
+    load_counter(&mut check, 0, Reg32::Reg0);
+    check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdP(ty, 0, RegS::from(1)))));
+    load_counter(&mut check, 1, Reg32::Reg1);
+    check.push(Instr::Cmp(CmpOp::GtA(SignFlag::Unsigned, RegA::A128, Reg32::Reg0, Reg32::Reg1)));
+    check[jif_first] = Instr::ControlFlow(ControlFlowOp::Jif(code_len(&check)));
+    check
+}
+
+/// Succeeds if the `u32` ids leading the values of global `ty` defined by
+/// the operation are strictly increasing and greater than the id of the
+/// latest value in the contract state. Ids are thus minted in increasing
+/// order, which keeps them unique without reading the whole contract state.
+/// Handles up to [`MAX_U256_STATES`] values.
+pub fn ids_increase(ty: u16, code: u8) -> Check {
+    let checked = IntFlags { signed: false, wrap: false };
+    let mut check = vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnG(ty, Reg16::Reg0))),
+    ];
+    at_most(&mut check, Reg32::Reg0, MAX_U256_STATES);
+    // `a128[1]` holds the lowest id the next value may have
+    check.extend([
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg3, Box::new(0u16.into()))),
+        Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg3, Box::new(u32::MAX.into()))),
+        Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg1, Box::new(0u8.into()))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnC(ty, Reg16::Reg1))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg4, Box::new(0u16.into()))),
+        Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg1, Reg32::Reg4)),
+    ]);
+    let jif_first = check.len();
+    check.push(Instr::ControlFlow(ControlFlowOp::Jif(0)));
+    check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdC(ty, 0, RegS::from(1)))));
+    load_counter(&mut check, 1, Reg32::Reg1);
+    check.push(Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg4, Box::new(1u8.into()))));
+    check.push(Instr::Arithmetic(ArithmeticOp::AddA(checked, RegA::A128, Reg32::Reg4, Reg32::Reg1)));
+    check[jif_first] = Instr::ControlFlow(ControlFlowOp::Jif(code_len(&check)));
+    unrolled(&mut check, Reg32::Reg0, MAX_U256_STATES, |index, offset| {
+        let mut body =
+            vec![Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdG(ty, index, RegS::from(0))))];
+        load_counter(&mut body, 0, Reg32::Reg0);
+        body.push(Instr::Cmp(CmpOp::LtA(SignFlag::Unsigned, RegA::A128, Reg32::Reg0, Reg32::Reg1)));
+        body.push(Instr::Cmp(CmpOp::StInv));
+        guard(&mut body);
+        let mut body = relocate(body, offset);
+        body.push(Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg1, Box::new(1u8.into()))));
+        body.push(Instr::Arithmetic(ArithmeticOp::AddA(checked, RegA::A128, Reg32::Reg0, Reg32::Reg1)));
+        body
+    });
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg0)));
+    check
+}
+
+/// Succeeds if the `u64` ids of the values of global `ty` defined by the
+/// operation are strictly increasing and each is the `u32` id leading a state
+/// spent of one of the `inputs` types, e.g. burned token ids. Together with
+/// [`spent_count_matches`] the ids are exactly those of the spent states.
+/// Handles up to [`MAX_ID_STATES`] values and states of each type.
+pub fn ids_spent(ty: u16, inputs: &[u16], code: u8) -> Check {
+    let checked = IntFlags { signed: false, wrap: false };
+    let mut check = vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnG(ty, Reg16::Reg0))),
+    ];
+    at_most(&mut check, Reg32::Reg0, MAX_ID_STATES);
+    // `a128[2]` holds the lowest id the next value may have
+    check.push(Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg3, Box::new(0u16.into()))));
+    check.push(Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg2, Box::new(0u8.into()))));
+    unrolled(&mut check, Reg32::Reg0, MAX_ID_STATES, |index, offset| {
+        let mut body = vec![
+            Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg3, Box::new(u64::MAX.into()))),
+            Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdG(ty, index, RegS::from(0)))),
+        ];
+        load_counter(&mut body, 0, Reg32::Reg0);
+        body.push(Instr::Cmp(CmpOp::LtA(SignFlag::Unsigned, RegA::A128, Reg32::Reg0, Reg32::Reg2)));
+        body.push(Instr::Cmp(CmpOp::StInv));
+        guard(&mut body);
+        body.push(Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg2, Box::new(1u8.into()))));
+        body.push(Instr::Arithmetic(ArithmeticOp::AddA(checked, RegA::A128, Reg32::Reg0, Reg32::Reg2)));
+        // `a16[7]` is set once a spent state with the id is found
+        body.push(Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg3, Box::new(u32::MAX.into()))));
+        body.push(Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg7, Box::new(0u16.into()))));
+        for ty in inputs {
+            body.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnP(*ty, Reg16::Reg5))));
+            at_most(&mut body, Reg32::Reg5, MAX_ID_STATES);
+            unrolled(&mut body, Reg32::Reg5, MAX_ID_STATES, |index, offset| {
+                let mut inner =
+                    vec![Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdP(*ty, index, RegS::from(1))))];
+                load_counter(&mut inner, 1, Reg32::Reg1);
+                inner.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A128, Reg32::Reg0, Reg32::Reg1)));
+                let jif_found = inner.len();
+                inner.push(Instr::ControlFlow(ControlFlowOp::Jif(0)));
+                let jmp_end = inner.len();
+                inner.push(Instr::ControlFlow(ControlFlowOp::Jmp(0)));
+                let found = code_len(&inner);
+                inner.push(Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg7, Box::new(1u16.into()))));
+                inner[jif_found] = Instr::ControlFlow(ControlFlowOp::Jif(found));
+                inner[jmp_end] = Instr::ControlFlow(ControlFlowOp::Jmp(code_len(&inner)));
+                // `st0` is set for the loop guard whether the id matched or not
+                inner.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg7, Reg32::Reg7)));
+                relocate(inner, offset)
+            });
+        }
+        body.push(Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg4, Box::new(1u16.into()))));
+        body.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg7, Reg32::Reg4)));
+        relocate(body, offset)
+    });
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg0)));
+    check
+}
+
+/// Succeeds if the `u64` leading the value of global `ty` defined by the
+/// operation equals its latest value in the contract state, or zero if
+/// there is none, increased by the number of `by` globals the operation
+/// defines, e.g. the total supply after minting tokens.
+pub fn supply_grows(ty: u16, by: u16, code: u8) -> Check { supply_moves(ty, by, true, code) }
+
+/// Succeeds if the `u64` leading the value of global `ty` defined by the
+/// operation equals its latest value in the contract state decreased by the
+/// number of `by` globals the operation defines, e.g. the total supply after
+/// burning tokens.
+pub fn supply_shrinks(ty: u16, by: u16, code: u8) -> Check { supply_moves(ty, by, false, code) }
+
+fn supply_moves(ty: u16, by: u16, grows: bool, code: u8) -> Check {
+    let checked = IntFlags { signed: false, wrap: false };
+    let mut check = vec![
+        errno(code),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg3, Box::new(0u16.into()))),
+        Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg3, Box::new(u64::MAX.into()))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdG(ty, 0, RegS::from(0)))),
+    ];
+    load_counter(&mut check, 0, Reg32::Reg0);
+    check.push(Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg1, Box::new(0u8.into()))));
+    check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnC(ty, Reg16::Reg1))));
+    check.push(Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg4, Box::new(0u16.into()))));
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg1, Reg32::Reg4)));
+    let jif_first = check.len();
+    check.push(Instr::ControlFlow(ControlFlowOp::Jif(0)));
+    check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdC(ty, 0, RegS::from(1)))));
+    load_counter(&mut check, 1, Reg32::Reg1);
+    check[jif_first] = Instr::ControlFlow(ControlFlowOp::Jif(code_len(&check)));
+    check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnG(by, Reg16::Reg2))));
+    check.push(Instr::Move(MovOp::CpyA(RegA::A16, Reg32::Reg2, RegA::A128, Reg32::Reg2)));
+    // the count is added to the side which is smaller
+    let side = if grows { Reg32::Reg1 } else { Reg32::Reg0 };
+    check.push(Instr::Arithmetic(ArithmeticOp::AddA(checked, RegA::A128, Reg32::Reg2, side)));
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A128, Reg32::Reg0, Reg32::Reg1)));
+    check
+}
+
+/// Succeeds if, in every value of global `ty` defined by the operation, the
+/// unsigned little-endian field of `bits` bits at byte `offset` does not
+/// exceed `max`, e.g. royalty basis points. The value must have at least 16
+/// bytes from `offset`. Handles up to [`MAX_U256_STATES`] values.
+pub fn globals_at_most(ty: u16, offset: u16, bits: u8, max: u64, code: u8) -> Check {
+    let mask = if bits >= 64 { u64::MAX } else { (1u64 << bits) - 1 };
+    let mut check = vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnG(ty, Reg16::Reg0))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg6, Box::new(offset.into()))),
+        Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg3, Box::new(mask.into()))),
+        Instr::Put(PutOp::PutA(RegA::A128, Reg32::Reg4, Box::new(max.into()))),
+    ];
+    at_most(&mut check, Reg32::Reg0, MAX_U256_STATES);
+    unrolled(&mut check, Reg32::Reg0, MAX_U256_STATES, |index, _| {
+        vec![
+            Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdG(ty, index, RegS::from(0)))),
+            Instr::Bytes(BytesOp::Extr(RegS::from(0), RegR::R128, Reg16::Reg0, Reg16::Reg6)),
+            Instr::Move(MovOp::SpyAR(RegA::A128, Reg32::Reg0, RegR::R128, Reg32::Reg0)),
+            Instr::Bitwise(BitwiseOp::And(RegA::A128.into(), Reg32::Reg0, Reg32::Reg3, Reg32::Reg0)),
+            Instr::Cmp(CmpOp::GtA(SignFlag::Unsigned, RegA::A128, Reg32::Reg0, Reg32::Reg4)),
+            Instr::Cmp(CmpOp::StInv),
+        ]
+    });
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg0)));
+    check
+}
+
+/// Succeeds if the latest value of a boolean contract global is `false`, or
+/// if the contract has no value of it. The index of `LdC` is a depth into
+/// the global state history as kept by rgb-core, 0 being the latest value,
+/// so the flag is read at index 0 rather than at the last index.
+pub fn flag_unset(ty: u16, code: u8) -> Check {
+    let mut check = vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnC(ty, Reg16::Reg0))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg1, Box::new(0u16.into()))),
+        Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg1)),
+    ];
+    let jif_none = check.len();
+    check.push(Instr::ControlFlow(ControlFlowOp::Jif(0)));
+    check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdC(ty, 0, RegS::from(0)))));
+    check.push(Instr::Bytes(BytesOp::Put(RegS::from(1), Box::new(ByteStr::with([0u8])), false)));
+    check.push(Instr::Bytes(BytesOp::Eq(RegS::from(0), RegS::from(1))));
+    check[jif_none] = Instr::ControlFlow(ControlFlowOp::Jif(code_len(&check)));
+    check
+}
+
+/// Succeeds if the operation sets a boolean global to `value`.
+pub fn sets_flag(ty: u16, value: bool, code: u8) -> Check {
+    vec![
+        errno(code),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdG(ty, 0, RegS::from(0)))),
+        Instr::Bytes(BytesOp::Put(RegS::from(1), Box::new(ByteStr::with([value as u8])), false)),
+        Instr::Bytes(BytesOp::Eq(RegS::from(0), RegS::from(1))),
+    ]
+}
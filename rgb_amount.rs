@@ -0,0 +1,131 @@
+//Decimal-aware token amounts
+use std::fmt::{self, Display, Formatter};
+
+use rgbstd::stl::Precision;
+use serde::{Serialize, Serializer};
+
+/// Largest number of decimals for which `10^decimals` fits into `U256`.
+pub const MAX_DECIMALS: u8 = 77;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum AmountError {
+    /// amount string is empty.
+    Empty,
+    /// invalid character '{0}' in amount.
+    InvalidChar(char),
+    /// amount has {found} fractional digits while the contract uses {max} decimals.
+    ExcessPrecision { found: usize, max: u8 },
+    /// amount does not fit into 256 bits.
+    Overflow,
+    /// {0} decimals exceed the maximum of 77.
+    TooManyDecimals(u8),
+}
+
+/// Number of decimals of a fungible contract, as stored in its `decimals`
+/// global. Converts between user-facing strings and base units.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Display)]
+#[display(inner)]
+pub struct Decimals(u8);
+
+impl Decimals {
+    pub fn with(decimals: u8) -> Result<Self, AmountError> {
+        if decimals > MAX_DECIMALS {
+            return Err(AmountError::TooManyDecimals(decimals));
+        }
+        Ok(Decimals(decimals))
+    }
+
+    pub fn count(self) -> u8 { self.0 }
+
+    /// Number of base units in one whole token, `10^decimals`.
+    pub fn scale(self) -> U256 {
+        (0..self.0).fold(U256::from(1u64), |scale, _| scale * U256::from(10u64))
+    }
+
+    /// Parses a user amount like `"1234.5678"` into base units. Fractional
+    /// digits beyond the contract decimals are rejected unless they are all
+    /// zeros.
+    pub fn parse(self, s: &str) -> Result<U256, AmountError> {
+        let s = s.trim();
+        let (int, frac) = match s.split_once('.') {
+            Some((int, frac)) => (int, frac),
+            None => (s, ""),
+        };
+        if int.is_empty() || (s.contains('.') && frac.is_empty()) {
+            return Err(AmountError::Empty);
+        }
+        if let Some(c) = int.chars().chain(frac.chars()).find(|c| !c.is_ascii_digit()) {
+            return Err(AmountError::InvalidChar(c));
+        }
+        let frac = frac.trim_end_matches('0');
+        if frac.len() > self.0 as usize {
+            return Err(AmountError::ExcessPrecision {
+                found: frac.len(),
+                max: self.0,
+            });
+        }
+        let padded = format!("{int}{frac:0<width$}", width = self.0 as usize);
+        padded.bytes().try_fold(U256::from(0u64), |value, digit| {
+            value
+                .checked_mul(U256::from(10u64))
+                .and_then(|value| value.checked_add(U256::from((digit - b'0') as u64)))
+                .ok_or(AmountError::Overflow)
+        })
+    }
+
+    /// Formats base units for display, without trailing fractional zeros.
+    pub fn format(self, value: U256) -> String {
+        let digits = value.to_string();
+        let decimals = self.0 as usize;
+        if decimals == 0 {
+            return digits;
+        }
+        let digits = format!("{digits:0>width$}", width = decimals + 1);
+        let (int, frac) = digits.split_at(digits.len() - decimals);
+        let frac = frac.trim_end_matches('0');
+        if frac.is_empty() {
+            return int.to_owned();
+        }
+        format!("{int}.{frac}")
+    }
+}
+
+impl TryFrom<u8> for Decimals {
+    type Error = AmountError;
+
+    fn try_from(decimals: u8) -> Result<Self, Self::Error> { Decimals::with(decimals) }
+}
+
+impl From<Precision> for Decimals {
+    fn from(precision: Precision) -> Self { Decimals(precision.decimals()) }
+}
+
+/// Amount in base units together with the decimals it is displayed with.
+/// Serializes to the decimal string, so JSON views show what the user typed
+/// rather than raw base units.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct DecimalAmount {
+    pub value: U256,
+    pub decimals: Decimals,
+}
+
+impl DecimalAmount {
+    pub fn with(value: U256, decimals: Decimals) -> Self { DecimalAmount { value, decimals } }
+
+    pub fn parse(s: &str, decimals: Decimals) -> Result<Self, AmountError> {
+        decimals.parse(s).map(|value| DecimalAmount::with(value, decimals))
+    }
+}
+
+impl Display for DecimalAmount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.decimals.format(self.value))
+    }
+}
+
+impl Serialize for DecimalAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
@@ -0,0 +1,501 @@
+//RGB-E1155
+use bp::bc::stl::bitcoin_stl;
+use strict_types::stl::std_stl;
+use strict_types::{CompileError, LibBuilder, TypeLib};
+
+use super::{
+    AssignIface, GenesisIface, GlobalIface, Iface, OwnedIface, Req, TransitionIface, VerNo,
+};
+use crate::interface::ArgSpec;
+use crate::rgb_abi::{self, AbiError, AbiReader, Selector, Token};
+use crate::stl::{rgb_contract_stl, StandardTypes};
+use std::collections::HashMap;
+
+pub const LIB_NAME_RGB_E1155: &str = "RGB-E1155";
+
+const SUPPLY_MISMATCH: u8 = 1;
+const NON_EQUAL_AMOUNTS: u8 = 2;
+const INVALID_PROOF: u8 = 3;
+const INSUFFICIENT_BALANCE: u8 = 4;
+const LENGTH_MISMATCH: u8 = 5;
+const NOT_APPROVED: u8 = 6;
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E1155, tags = repr, into_u8, try_from_u8)]
+#[repr(u8)]
+pub enum Error {
+    #[strict_type(dumb)]
+    SupplyMismatch = SUPPLY_MISMATCH,
+    NonEqualAmounts = NON_EQUAL_AMOUNTS,
+    InvalidProof = INVALID_PROOF,
+    InsufficientBalance = INSUFFICIENT_BALANCE,
+    LengthMismatch = LENGTH_MISMATCH,
+    NotApproved = NOT_APPROVED,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E1155)]
+pub struct Balance {
+    pub token_id: U256,
+    pub amount: U256,
+}
+
+/// ERC-1155 operator approval, assigned back to the owner's seal together
+/// with the balances spent to authorize it.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E1155)]
+pub struct Operator {
+    pub owner: Account,
+    pub operator: Account,
+    pub approved: bool,
+}
+
+type Balances = HashMap<(Account, U256), U256>;
+type OperatorApprovals = HashMap<Account, HashMap<Account, bool>>;
+
+#[derive(Wrapper, WrapperMut, Clone, Eq, PartialEq, Debug)]
+#[wrapper(Deref)]
+#[wrapper_mut(DerefMut)]
+pub struct RgbE1155 {
+    name: String,
+    symbol: String,
+    owner: Account,
+    uri: String,
+}
+
+fn _rgb_e1155_stl() -> Result<TypeLib, CompileError> {
+    LibBuilder::new(libname!(LIB_NAME_RGB_E1155), tiny_bset! {
+        std_stl().to_dependency(),
+        bitcoin_stl().to_dependency(),
+        rgb_contract_stl().to_dependency()
+    })
+    .transpile::<Balance>()
+    .transpile::<Operator>()
+    .transpile::<Error>()
+    .compile()
+}
+
+pub fn rgb_e1155_stl() -> TypeLib {
+    _rgb_e1155_stl().expect("invalid strict type RGB-E1155 library")
+}
+
+pub fn rgb_e1155() -> Iface {
+    let types = StandardTypes::with(rgb_e1155_stl());
+
+    Iface {
+        version: VerNo::V1,
+        name: tn!("RGB-E1155"),
+        global_state: tiny_bmap! {
+            fname!("name") => GlobalIface::required(types.get("RGBContract.Name")),
+            fname!("symbol") => GlobalIface::required(types.get("RGBContract.Data")),
+            fname!("uri") => GlobalIface::required(types.get("RGBContract.Details")),
+            fname!("tokens") => GlobalIface::none_or_many(types.get("RGBContract.TokenData")),
+        },
+        assignments: tiny_bmap! {
+            fname!("beneficiary") => AssignIface::public(OwnedIface::Data(types.get("RGB-E1155.Balance")), Req::OneOrMore),
+            fname!("operator") => AssignIface::public(OwnedIface::Data(types.get("RGB-E1155.Operator")), Req::NoneOrMore),
+        },
+        genesis: GenesisIface {
+            metadata: Some(types.get("RGBContract.Meta")),
+            global: tiny_bmap! {
+                fname!("init") => ArgSpec::required(),
+            },
+            assignments: none!(),
+            valencies: none!(),
+            errors: tiny_bset! {
+                SUPPLY_MISMATCH
+            },
+        },
+        transitions: tiny_bmap! {
+            tn!("SafeTransferFrom") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("from") => ArgSpec::from_required("Account"),
+                    fname!("to") => ArgSpec::from_required("Account"),
+                    fname!("id") => ArgSpec::from_required("Index"),
+                    fname!("amount") => ArgSpec::from_required("amount"),
+                    fname!("data") => ArgSpec::optional(),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    NON_EQUAL_AMOUNTS,
+                    INSUFFICIENT_BALANCE,
+                    NOT_APPROVED
+                },
+                default_assignment: Some(fname!("beneficiary")),
+            },
+            tn!("SafeBatchTransferFrom") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("from") => ArgSpec::from_required("Account"),
+                    fname!("to") => ArgSpec::from_required("Account"),
+                    fname!("ids") => ArgSpec::from_non_empty("Index"),
+                    fname!("amounts") => ArgSpec::from_non_empty("amount"),
+                    fname!("data") => ArgSpec::optional(),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    NON_EQUAL_AMOUNTS,
+                    INSUFFICIENT_BALANCE,
+                    LENGTH_MISMATCH,
+                    NOT_APPROVED
+                },
+                default_assignment: Some(fname!("beneficiary")),
+            },
+            tn!("BalanceOf") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("account") => ArgSpec::from_required("Account"),
+                    fname!("id") => ArgSpec::from_required("Index"),
+                },
+                valencies: none!(),
+                errors: none!(),
+                default_assignment: Some(fname!("balance_of")),
+            },
+            tn!("BalanceOfBatch") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("accounts") => ArgSpec::from_non_empty("Account"),
+                    fname!("ids") => ArgSpec::from_non_empty("Index"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    LENGTH_MISMATCH
+                },
+                default_assignment: Some(fname!("balance_of_batch")),
+            },
+            tn!("SetApprovalForAll") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("operator") => ArgSpec::from_required("Account"),
+                    fname!("approved") => ArgSpec::from_required("Bool"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    INVALID_PROOF,
+                    NOT_APPROVED
+                },
+                default_assignment: Some(fname!("set_approval_for_all")),
+            },
+            tn!("IsApprovedForAll") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("account") => ArgSpec::from_required("Account"),
+                    fname!("operator") => ArgSpec::from_required("Account"),
+                },
+                valencies: none!(),
+                errors: none!(),
+                default_assignment: Some(fname!("is_approved_for_all")),
+            },
+            tn!("Uri") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("id") => ArgSpec::from_required("Index"),
+                },
+                valencies: none!(),
+                errors: none!(),
+                default_assignment: Some(fname!("uri")),
+            },
+        },
+        extensions: none!(),
+        error_type: types.get("RGB-E1155.Error"),
+        default_operation: Some(tn!("SafeTransferFrom")),
+    }
+}
+
+pub trait RgbE1155Iface {
+    //Genesis
+    fn init(&self, name: &str, symbol: &str, uri: &str);
+    //Transaction
+    fn safe_transfer_from(from: Account, to: Account, id: U256, amount: U256, data: &[u8]);
+    fn safe_batch_transfer_from(
+        from: Account,
+        to: Account,
+        ids: &[U256],
+        amounts: &[U256],
+        data: &[u8],
+    );
+    fn balance_of(account: Account, id: U256) -> U256;
+    fn balance_of_batch(accounts: &[Account], ids: &[U256]) -> Vec<U256>;
+    fn set_approval_for_all(operator: Account, approved: bool);
+    fn is_approved_for_all(account: Account, operator: Account) -> bool;
+    //Global metadata
+    fn name() -> String;
+    fn symbol() -> String;
+    fn uri(id: U256) -> String;
+    //Introspection
+    fn supports_interface(interface_id: [u8; 4]) -> bool;
+}
+
+pub const SEL_SAFE_TRANSFER_FROM: Selector = [0xf2, 0x42, 0x43, 0x2a];
+pub const SEL_SAFE_BATCH_TRANSFER_FROM: Selector = [0x2e, 0xb2, 0xc2, 0xd6];
+pub const SEL_BALANCE_OF: Selector = [0x00, 0xfd, 0xd5, 0x8e];
+pub const SEL_BALANCE_OF_BATCH: Selector = [0x4e, 0x12, 0x73, 0xf4];
+pub const SEL_SET_APPROVAL_FOR_ALL: Selector = [0xa2, 0x2c, 0xb4, 0x65];
+pub const SEL_IS_APPROVED_FOR_ALL: Selector = [0xe9, 0x85, 0xe9, 0xc5];
+pub const SEL_URI: Selector = [0x0e, 0x89, 0x34, 0x1c];
+
+/// ABI-encoded calls of the ERC-1155 methods mapped onto [`rgb_e1155`]
+/// operations.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum RgbE1155Call {
+    SafeTransferFrom {
+        from: Account,
+        to: Account,
+        id: U256,
+        amount: U256,
+        data: Vec<u8>,
+    },
+    SafeBatchTransferFrom {
+        from: Account,
+        to: Account,
+        ids: Vec<U256>,
+        amounts: Vec<U256>,
+        data: Vec<u8>,
+    },
+    BalanceOf { account: Account, id: U256 },
+    BalanceOfBatch { accounts: Vec<Account>, ids: Vec<U256> },
+    SetApprovalForAll { operator: Account, approved: bool },
+    IsApprovedForAll { account: Account, operator: Account },
+    Uri { id: U256 },
+}
+
+impl RgbE1155Call {
+    pub fn selector(&self) -> Selector {
+        match self {
+            RgbE1155Call::SafeTransferFrom { .. } => SEL_SAFE_TRANSFER_FROM,
+            RgbE1155Call::SafeBatchTransferFrom { .. } => SEL_SAFE_BATCH_TRANSFER_FROM,
+            RgbE1155Call::BalanceOf { .. } => SEL_BALANCE_OF,
+            RgbE1155Call::BalanceOfBatch { .. } => SEL_BALANCE_OF_BATCH,
+            RgbE1155Call::SetApprovalForAll { .. } => SEL_SET_APPROVAL_FOR_ALL,
+            RgbE1155Call::IsApprovedForAll { .. } => SEL_IS_APPROVED_FOR_ALL,
+            RgbE1155Call::Uri { .. } => SEL_URI,
+        }
+    }
+
+    /// Name of the [`rgb_e1155`] operation the call maps to.
+    pub fn operation(&self) -> &'static str {
+        match self {
+            RgbE1155Call::SafeTransferFrom { .. } => "SafeTransferFrom",
+            RgbE1155Call::SafeBatchTransferFrom { .. } => "SafeBatchTransferFrom",
+            RgbE1155Call::BalanceOf { .. } => "BalanceOf",
+            RgbE1155Call::BalanceOfBatch { .. } => "BalanceOfBatch",
+            RgbE1155Call::SetApprovalForAll { .. } => "SetApprovalForAll",
+            RgbE1155Call::IsApprovedForAll { .. } => "IsApprovedForAll",
+            RgbE1155Call::Uri { .. } => "Uri",
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let tokens = match self {
+            RgbE1155Call::SafeTransferFrom { from, to, id, amount, data } => vec![
+                Token::address(from),
+                Token::address(to),
+                Token::u256(*id),
+                Token::u256(*amount),
+                Token::Bytes(data.clone()),
+            ],
+            RgbE1155Call::SafeBatchTransferFrom { from, to, ids, amounts, data } => vec![
+                Token::address(from),
+                Token::address(to),
+                Token::Array(ids.iter().copied().map(Token::u256).collect()),
+                Token::Array(amounts.iter().copied().map(Token::u256).collect()),
+                Token::Bytes(data.clone()),
+            ],
+            RgbE1155Call::BalanceOf { account, id } => {
+                vec![Token::address(account), Token::u256(*id)]
+            }
+            RgbE1155Call::BalanceOfBatch { accounts, ids } => vec![
+                Token::Array(accounts.iter().map(Token::address).collect()),
+                Token::Array(ids.iter().copied().map(Token::u256).collect()),
+            ],
+            RgbE1155Call::SetApprovalForAll { operator, approved } => {
+                vec![Token::address(operator), Token::bool(*approved)]
+            }
+            RgbE1155Call::IsApprovedForAll { account, operator } => {
+                vec![Token::address(account), Token::address(operator)]
+            }
+            RgbE1155Call::Uri { id } => vec![Token::u256(*id)],
+        };
+        rgb_abi::encode_call(self.selector(), &tokens)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, AbiError> {
+        let (selector, mut r) = rgb_abi::split_selector(data)?;
+        let call = match selector {
+            SEL_SAFE_TRANSFER_FROM => RgbE1155Call::SafeTransferFrom {
+                from: r.address()?,
+                to: r.address()?,
+                id: r.u256()?,
+                amount: r.u256()?,
+                data: r.bytes()?,
+            },
+            SEL_SAFE_BATCH_TRANSFER_FROM => {
+                let from = r.address()?;
+                let to = r.address()?;
+                let ids = r.array(AbiReader::u256)?;
+                let amounts = r.array(AbiReader::u256)?;
+                if ids.len() != amounts.len() {
+                    return Err(AbiError::LengthMismatch(ids.len(), amounts.len()));
+                }
+                RgbE1155Call::SafeBatchTransferFrom { from, to, ids, amounts, data: r.bytes()? }
+            }
+            SEL_BALANCE_OF => RgbE1155Call::BalanceOf {
+                account: r.address()?,
+                id: r.u256()?,
+            },
+            SEL_BALANCE_OF_BATCH => {
+                let accounts = r.array(AbiReader::address)?;
+                let ids = r.array(AbiReader::u256)?;
+                if accounts.len() != ids.len() {
+                    return Err(AbiError::LengthMismatch(accounts.len(), ids.len()));
+                }
+                RgbE1155Call::BalanceOfBatch { accounts, ids }
+            }
+            SEL_SET_APPROVAL_FOR_ALL => RgbE1155Call::SetApprovalForAll {
+                operator: r.address()?,
+                approved: r.bool()?,
+            },
+            SEL_IS_APPROVED_FOR_ALL => RgbE1155Call::IsApprovedForAll {
+                account: r.address()?,
+                operator: r.address()?,
+            },
+            SEL_URI => RgbE1155Call::Uri { id: r.u256()? },
+            unknown => return Err(AbiError::UnknownSelector(unknown)),
+        };
+        Ok(call)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn word(value: u64) -> [u8; 32] { U256::from(value).to_be_bytes() }
+
+    fn batch() -> RgbE1155Call {
+        RgbE1155Call::SafeBatchTransferFrom {
+            from: Account::from([0x11; 20]),
+            to: Account::from([0x22; 20]),
+            ids: vec![U256::from(1u64), U256::from(2u64)],
+            amounts: vec![U256::from(3u64), U256::from(4u64)],
+            data: vec![0xab, 0xcd, 0xef],
+        }
+    }
+
+    #[test]
+    fn batch_transfer_layout() {
+        let data = batch().encode();
+        assert_eq!(&data[..4], &SEL_SAFE_BATCH_TRANSFER_FROM);
+        let args = &data[4..];
+        // five head words, then each dynamic argument as length and items
+        assert_eq!(&args[64..96], &word(160));
+        assert_eq!(&args[96..128], &word(256));
+        assert_eq!(&args[128..160], &word(352));
+        assert_eq!(&args[160..192], &word(2));
+        assert_eq!(&args[192..224], &word(1));
+        assert_eq!(&args[224..256], &word(2));
+        assert_eq!(&args[256..288], &word(2));
+        assert_eq!(&args[320..352], &word(4));
+        assert_eq!(&args[352..384], &word(3));
+        // bytes are padded to a full word
+        assert_eq!(&args[384..387], &[0xab, 0xcd, 0xef]);
+        assert_eq!(&args[387..], &[0u8; 29]);
+        assert_eq!(RgbE1155Call::decode(&data), Ok(batch()));
+    }
+
+    #[test]
+    fn round_trip() {
+        let calls = [
+            RgbE1155Call::SafeTransferFrom {
+                from: Account::from([0x11; 20]),
+                to: Account::from([0x22; 20]),
+                id: U256::MAX,
+                amount: U256::from(u64::MAX) + U256::from(1u64),
+                data: vec![],
+            },
+            RgbE1155Call::SafeTransferFrom {
+                from: Account::from([0x11; 20]),
+                to: Account::from([0x22; 20]),
+                id: U256::from(7u64),
+                amount: U256::from(1u64),
+                data: vec![0x5a; 33],
+            },
+            RgbE1155Call::SafeBatchTransferFrom {
+                from: Account::from([0x11; 20]),
+                to: Account::from([0x22; 20]),
+                ids: vec![],
+                amounts: vec![],
+                data: vec![],
+            },
+            RgbE1155Call::BalanceOfBatch {
+                accounts: vec![Account::from([0x11; 20]), Account::from([0x22; 20])],
+                ids: vec![U256::from(1u64), U256::from(2u64)],
+            },
+            RgbE1155Call::SetApprovalForAll {
+                operator: Account::from([0x33; 20]),
+                approved: true,
+            },
+            RgbE1155Call::Uri { id: U256::from(5u64) },
+        ];
+        for call in calls {
+            assert_eq!(RgbE1155Call::decode(&call.encode()), Ok(call));
+        }
+    }
+
+    #[test]
+    fn length_mismatch() {
+        let data = rgb_abi::encode_call(SEL_SAFE_BATCH_TRANSFER_FROM, &[
+            Token::address(&Account::from([0x11; 20])),
+            Token::address(&Account::from([0x22; 20])),
+            Token::Array(vec![Token::u64(1), Token::u64(2)]),
+            Token::Array(vec![Token::u64(3)]),
+            Token::Bytes(vec![]),
+        ]);
+        assert_eq!(RgbE1155Call::decode(&data), Err(AbiError::LengthMismatch(2, 1)));
+
+        let data = rgb_abi::encode_call(SEL_BALANCE_OF_BATCH, &[
+            Token::Array(vec![Token::address(&Account::from([0x11; 20]))]),
+            Token::Array(vec![]),
+        ]);
+        assert_eq!(RgbE1155Call::decode(&data), Err(AbiError::LengthMismatch(1, 0)));
+    }
+
+    #[test]
+    fn malformed() {
+        let data = batch().encode();
+        // cut off inside the data bytes
+        assert!(matches!(
+            RgbE1155Call::decode(&data[..4 + 384 + 2]),
+            Err(AbiError::Truncated(_))
+        ));
+        assert_eq!(RgbE1155Call::decode(&data[..3]), Err(AbiError::NoSelector));
+        assert_eq!(
+            RgbE1155Call::decode(&[0xde, 0xad, 0xbe, 0xef]),
+            Err(AbiError::UnknownSelector([0xde, 0xad, 0xbe, 0xef]))
+        );
+
+        let data = rgb_abi::encode_call(SEL_SET_APPROVAL_FOR_ALL, &[
+            Token::address(&Account::from([0x33; 20])),
+            Token::u64(2),
+        ]);
+        assert_eq!(RgbE1155Call::decode(&data), Err(AbiError::InvalidBool(32)));
+    }
+}
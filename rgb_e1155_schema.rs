@@ -0,0 +1,190 @@
+///RGB-E1155 SCHEMA
+use aluvm::library::LibSite;
+use rgbstd::interface::rgb_e1155::Error;
+use rgbstd::interface::{rgb_e1155, rgb_e1155_stl, IfaceImpl, NamedField, NamedType, VerNo};
+use rgbstd::schema::{
+    GenesisSchema, GlobalStateSchema, Occurrences, Schema, Script, StateSchema, SubSchema,
+    TransitionSchema,
+};
+use rgbstd::stl::StandardTypes;
+use rgbstd::vm::{AluScript, EntryPoint};
+use strict_types::{SemId, Ty};
+
+use crate::rgb_alu::{all_of, conserves_u256_by_id, keeps_count, reassigns_unchanged};
+
+const GS_URI: u16 = 11550;
+const GS_TOKENS: u16 = 11551;
+const OS_BALANCE: u16 = 11550;
+const OS_OPERATOR: u16 = 11551;
+const TS_SAFE_TRANSFER_FROM: u16 = 11550;
+const TS_SAFE_BATCH_TRANSFER_FROM: u16 = 11551;
+const TS_BALANCE_OF: u16 = 11552;
+const TS_BALANCE_OF_BATCH: u16 = 11553;
+const TS_SET_APPROVAL_FOR_ALL: u16 = 11554;
+const TS_IS_APPROVED_FOR_ALL: u16 = 11555;
+const TS_URI: u16 = 11556;
+
+pub fn mta_schema() -> SubSchema {
+    let types = StandardTypes::with(rgb_e1155_stl());
+
+    // balances move per token id; an operator right used to authorize a
+    // transfer must be kept, so using it does not revoke the approval
+    let transfer_lib = all_of([
+        conserves_u256_by_id(OS_BALANCE, Error::NonEqualAmounts as u8),
+        keeps_count(OS_OPERATOR, Error::NotApproved as u8),
+    ]);
+    let transfer_id = transfer_lib.id();
+    // the owner authenticates the approval by spending their balances and
+    // assigning them back unchanged, as ERC-721 approvals do with the token
+    let approve_all_lib = all_of([reassigns_unchanged(OS_BALANCE, Error::NotApproved as u8)]);
+    let approve_all_id = approve_all_lib.id();
+    Schema {
+        ffv: zero!(),
+        subset_of: None,
+        type_system: types.type_system(),
+        global_types: tiny_bmap! {
+            GS_NAME => GlobalStateSchema::once(types.get("RGBContract.Text")),
+            GS_SYMBOL => GlobalStateSchema::once(types.get("RGBContract.Text")),
+            GS_URI => GlobalStateSchema::once(types.get("RGBContract.Details")),
+            GS_TOKENS => GlobalStateSchema::many(types.get("RGBContract.TokenData")),
+        },
+        owned_types: tiny_bmap! {
+            OS_BALANCE => StateSchema::Structured(types.get("RGB-E1155.Balance")),
+            OS_OPERATOR => StateSchema::Structured(types.get("RGB-E1155.Operator")),
+        },
+        valency_types: none!(),
+        genesis: GenesisSchema {
+            metadata: Ty::<SemId>::UNIT.id(None),
+            globals: tiny_bmap! {
+                GS_INIT => Occurrences::Once,
+                GS_TOKENS => Occurrences::OnceOrMore,
+            },
+            assignments: tiny_bmap! {
+                OS_BALANCE => Occurrences::OnceOrMore,
+            },
+            valencies: none!(),
+        },
+        extensions: none!(),
+        transitions: tiny_bmap! {
+            TS_SAFE_TRANSFER_FROM => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    OS_BALANCE => Occurrences::OnceOrMore,
+                    OS_OPERATOR => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_BALANCE => Occurrences::OnceOrMore,
+                    OS_OPERATOR => Occurrences::NoneOrOnce
+                },
+                valencies: none!(),
+            },
+            TS_SAFE_BATCH_TRANSFER_FROM => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    OS_BALANCE => Occurrences::OnceOrMore,
+                    OS_OPERATOR => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_BALANCE => Occurrences::OnceOrMore,
+                    OS_OPERATOR => Occurrences::NoneOrOnce
+                },
+                valencies: none!(),
+            },
+            TS_BALANCE_OF => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    OS_BALANCE => Occurrences::OnceOrMore
+                },
+                assignments: none!(),
+                valencies: none!(),
+            },
+            TS_BALANCE_OF_BATCH => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    OS_BALANCE => Occurrences::OnceOrMore
+                },
+                assignments: none!(),
+                valencies: none!(),
+            },
+            TS_SET_APPROVAL_FOR_ALL => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    OS_BALANCE => Occurrences::OnceOrMore,
+                    OS_OPERATOR => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_BALANCE => Occurrences::OnceOrMore,
+                    OS_OPERATOR => Occurrences::NoneOrOnce
+                },
+                valencies: none!(),
+            },
+            TS_IS_APPROVED_FOR_ALL => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    OS_OPERATOR => Occurrences::Once
+                },
+                assignments: none!(),
+                valencies: none!(),
+            },
+            TS_URI => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: none!(),
+                assignments: none!(),
+                valencies: none!(),
+            },
+        },
+        script: Script::AluVM(AluScript {
+            libs: confined_bmap! {
+                transfer_id => transfer_lib,
+                approve_all_id => approve_all_lib,
+            },
+            entry_points: confined_bmap! {
+                EntryPoint::ValidateTransition(TS_SAFE_TRANSFER_FROM) => LibSite::with(0, transfer_id),
+                EntryPoint::ValidateTransition(TS_SAFE_BATCH_TRANSFER_FROM) => LibSite::with(0, transfer_id),
+                EntryPoint::ValidateTransition(TS_SET_APPROVAL_FOR_ALL) => LibSite::with(0, approve_all_id),
+            },
+        }),
+    }
+}
+
+pub fn mta_rgb_e1155() -> IfaceImpl {
+    let schema = mta_schema();
+    let iface = rgb_e1155();
+
+    IfaceImpl {
+        version: VerNo::V1,
+        schema_id: schema.schema_id(),
+        iface_id: iface.iface_id(),
+        genesis_iface: tiny_bset! {
+            NamedField::with(GS_INIT, fname!("init")),
+        },
+        global_state: tiny_bset! {
+            NamedField::with(GS_NAME, fname!("name")),
+            NamedField::with(GS_SYMBOL, fname!("symbol")),
+            NamedField::with(GS_URI, fname!("uri")),
+            NamedField::with(GS_TOKENS, fname!("tokens")),
+        },
+        assignments: tiny_bset! {
+            NamedField::with(OS_BALANCE, fname!("beneficiary")),
+            NamedField::with(OS_OPERATOR, fname!("operator")),
+        },
+        valencies: none!(),
+        transitions: tiny_bset! {
+            NamedType::with(TS_SAFE_TRANSFER_FROM, tn!("safe_transfer_from")),
+            NamedType::with(TS_SAFE_BATCH_TRANSFER_FROM, tn!("safe_batch_transfer_from")),
+            NamedType::with(TS_BALANCE_OF, tn!("balance_of")),
+            NamedType::with(TS_BALANCE_OF_BATCH, tn!("balance_of_batch")),
+            NamedType::with(TS_SET_APPROVAL_FOR_ALL, tn!("set_approval_for_all")),
+            NamedType::with(TS_IS_APPROVED_FOR_ALL, tn!("is_approved_for_all")),
+            NamedType::with(TS_URI, tn!("uri")),
+        },
+        extensions: none!(),
+    }
+}
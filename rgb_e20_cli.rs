@@ -0,0 +1,271 @@
+//RGB-E20 CLI
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use amplify::confinement::U32;
+use bp::Outpoint;
+use clap::{Parser, Subcommand};
+use rgbstd::contract::{ContractId, GraphSeal, TypedState};
+use rgbstd::persistence::{Inventory, Stock};
+use rgbstd::schema::AssignmentType;
+use rgbstd::stl::Precision;
+use serde::Serialize;
+use strict_encoding::{StrictDeserialize, StrictSerialize};
+
+use crate::rgb_amount::{AmountError, DecimalAmount, Decimals};
+use crate::rgb_e20_interface::{checked_sum, rgb_e20, Amount256, Error};
+use crate::rgb_e20_invoice::{E20Invoice, InvoiceError};
+use crate::rgb_e20_selection::{select, Holding, Strategy};
+use crate::rgb_e20_transfer::TransferError;
+
+const STOCK_FILE: &str = "stock.dat";
+
+#[derive(Parser, Clone, Debug)]
+#[command(name = "rgb-e20", about = "Inspect and transfer RGB-E20 tokens")]
+pub struct Opts {
+    /// Directory holding the local stash.
+    #[arg(short, long, global = true, default_value = "~/.rgb-e20")]
+    pub data_dir: PathBuf,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// Amounts are always read and written with the decimals of the contract,
+/// e.g. `1.5` for 1.5 tokens, never in base units.
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Print the allocations held by the given outpoints as JSON.
+    Balance {
+        contract_id: ContractId,
+        /// Outpoints in `<txid>:<vout>` form.
+        #[arg(required = true)]
+        outpoints: Vec<String>,
+    },
+    /// Print an invoice requesting `amount` tokens to be paid to a seal.
+    Invoice {
+        contract_id: ContractId,
+        amount: String,
+        /// Beneficiary seal in `<txid>:<vout>` form.
+        beneficiary: String,
+    },
+    /// Prepare a `BatchTransfer` transition paying an invoice from the
+    /// allocations held by `--from`.
+    Pay {
+        invoice: String,
+        /// Outpoints holding the allocations to spend.
+        #[arg(long, required = true)]
+        from: Vec<String>,
+        /// Seal receiving the change, in `<txid>:<vout>` form.
+        #[arg(long)]
+        change: String,
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+}
+
+#[derive(Debug, Display, Error, From)]
+#[display(inner)]
+pub enum CliError {
+    #[from]
+    Io(std::io::Error),
+    #[from]
+    Json(serde_json::Error),
+    #[from]
+    Amount(AmountError),
+    #[from]
+    Invoice(InvoiceError),
+    #[from]
+    Transfer(TransferError),
+    #[display("invalid amount: {0:?}")]
+    Contract(Error),
+    #[display("contract {0} has no decimals")]
+    NoDecimals(ContractId),
+    #[display("invalid seal {0}")]
+    InvalidSeal(String),
+    #[display("stash error: {0}")]
+    Stash(String),
+}
+
+/// JSON view of an allocation, with the amount in contract decimals.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct AllocationView {
+    pub outpoint: String,
+    pub amount: DecimalAmount,
+}
+
+/// JSON view of the allocations held by a set of outpoints.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct BalanceView {
+    pub contract_id: String,
+    pub decimals: u8,
+    pub allocations: Vec<AllocationView>,
+    pub total: DecimalAmount,
+}
+
+fn expand(path: &Path) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(&path.to_string_lossy()).to_string())
+}
+
+fn load_stock(data_dir: &Path) -> Result<Stock, CliError> {
+    let path = data_dir.join(STOCK_FILE);
+    if !path.exists() {
+        return Ok(Stock::default());
+    }
+    Stock::strict_deserialize_from_file::<U32>(path).map_err(|e| CliError::Stash(e.to_string()))
+}
+
+fn store_stock(data_dir: &Path, stock: &Stock) -> Result<(), CliError> {
+    stock
+        .strict_serialize_to_file::<U32>(data_dir.join(STOCK_FILE))
+        .map_err(|e| CliError::Stash(e.to_string()))
+}
+
+fn parse_outpoint(s: &str) -> Result<Outpoint, CliError> {
+    s.parse().map_err(|_| CliError::InvalidSeal(s.to_owned()))
+}
+
+/// Decimals of a contract, from its `decimals` global.
+fn decimals(stock: &Stock, contract_id: ContractId) -> Result<Decimals, CliError> {
+    let contract = stock
+        .contract_iface(contract_id, rgb_e20().iface_id())
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    contract
+        .global("decimals")
+        .map_err(|e| CliError::Stash(e.to_string()))?
+        .first()
+        .map(|val| Decimals::from(Precision::from_strict_val_unchecked(val)))
+        .ok_or(CliError::NoDecimals(contract_id))
+}
+
+fn assets_type(stock: &mut Stock, contract_id: ContractId) -> Result<AssignmentType, CliError> {
+    stock
+        .transition_builder(contract_id, rgb_e20().name, Some(tn!("BatchTransfer")))
+        .map_err(|e| CliError::Stash(e.to_string()))?
+        .assignments_type(&fname!("beneficiary"))
+        .ok_or_else(|| CliError::Stash(s!("contract has no beneficiary assignment")))
+}
+
+/// `beneficiary` allocations held by the given outpoints.
+fn holdings(
+    stock: &mut Stock,
+    contract_id: ContractId,
+    outpoints: &[Outpoint],
+) -> Result<Vec<Holding>, CliError> {
+    let ty = assets_type(stock, contract_id)?;
+    let mut holdings = vec![];
+    for outpoint in outpoints {
+        let state = stock
+            .state_for_outpoints(contract_id, [*outpoint])
+            .map_err(|e| CliError::Stash(e.to_string()))?;
+        for (opout, state) in state {
+            let TypedState::Structured(data) = state else {
+                continue;
+            };
+            if opout.ty != ty {
+                continue;
+            }
+            let amount = <[u8; 32]>::try_from(data.as_ref())
+                .map(Amount256::from)
+                .map_err(|_| CliError::Stash(format!("invalid allocation {opout}")))?;
+            holdings.push(Holding {
+                opout,
+                outpoint: *outpoint,
+                amount,
+            });
+        }
+    }
+    Ok(holdings)
+}
+
+fn balance(
+    stock: &mut Stock,
+    contract_id: ContractId,
+    outpoints: &[Outpoint],
+) -> Result<BalanceView, CliError> {
+    let decimals = decimals(stock, contract_id)?;
+    let holdings = holdings(stock, contract_id, outpoints)?;
+    let total = checked_sum(holdings.iter().map(|holding| holding.amount))
+        .map_err(CliError::Contract)?;
+    Ok(BalanceView {
+        contract_id: contract_id.to_string(),
+        decimals: decimals.count(),
+        allocations: holdings
+            .iter()
+            .map(|holding| AllocationView {
+                outpoint: holding.outpoint.to_string(),
+                amount: DecimalAmount::with(holding.amount.value(), decimals),
+            })
+            .collect(),
+        total: DecimalAmount::with(total.value(), decimals),
+    })
+}
+
+fn pay(
+    stock: &mut Stock,
+    invoice: &str,
+    from: &[Outpoint],
+    change: Outpoint,
+    out: &Path,
+) -> Result<(), CliError> {
+    let invoice = E20Invoice::parse(invoice, |contract_id| decimals(stock, contract_id).ok())?;
+    let contract_id = invoice.contract_id;
+    let holdings = holdings(stock, contract_id, from)?;
+    let target = Amount256::with(invoice.amount.value);
+    let selection = select(holdings, target, Strategy::default(), GraphSeal::from(change))
+        .map_err(CliError::Contract)?;
+    let transition = selection
+        .transfer(contract_id)
+        .pay(GraphSeal::from(invoice.beneficiary), target)
+        .compose(stock)?;
+    transition
+        .strict_serialize_to_file::<U32>(out)
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    eprintln!(
+        "Transfer of {} to {} written to {}",
+        invoice.amount,
+        invoice.beneficiary,
+        out.display()
+    );
+    Ok(())
+}
+
+pub fn exec(opts: Opts) -> Result<(), CliError> {
+    let data_dir = expand(&opts.data_dir);
+    fs::create_dir_all(&data_dir)?;
+    let mut stock = load_stock(&data_dir)?;
+
+    match opts.command {
+        Command::Balance { contract_id, outpoints } => {
+            let outpoints =
+                outpoints.iter().map(|s| parse_outpoint(s)).collect::<Result<Vec<_>, _>>()?;
+            let view = balance(&mut stock, contract_id, &outpoints)?;
+            println!("{}", serde_json::to_string_pretty(&view)?);
+        }
+        Command::Invoice { contract_id, amount, beneficiary } => {
+            let amount = DecimalAmount::parse(&amount, decimals(&stock, contract_id)?)?;
+            if amount.value == U256::from(0u64) {
+                return Err(InvoiceError::ZeroAmount.into());
+            }
+            let invoice = E20Invoice {
+                contract_id,
+                amount,
+                beneficiary: parse_outpoint(&beneficiary)?,
+            };
+            println!("{invoice}");
+        }
+        Command::Pay { invoice, from, change, out } => {
+            let from = from.iter().map(|s| parse_outpoint(s)).collect::<Result<Vec<_>, _>>()?;
+            pay(&mut stock, &invoice, &from, parse_outpoint(&change)?, &out)?;
+        }
+    }
+
+    store_stock(&data_dir, &stock)
+}
+
+fn main() {
+    if let Err(err) = exec(Opts::parse()) {
+        eprintln!("rgb-e20: {err}");
+        std::process::exit(1);
+    }
+}
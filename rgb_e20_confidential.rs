@@ -0,0 +1,161 @@
+//RGB-E20 confidential amounts
+use std::collections::BTreeSet;
+
+use rand::{CryptoRng, RngCore};
+use rgbstd::contract::{BlindingFactor, ConcealedValue, RevealedValue};
+use rgbstd::Conceal;
+use secp256k1_zkp::{Scalar, SecretKey};
+
+use crate::rgb_e20_interface::{checked_sum, Amount256, Error};
+
+/// Largest amount a confidential allocation can hold. Bulletproofs prove a
+/// 64-bit range, so hidden amounts are capped at `u64::MAX` while public ones
+/// are 256-bit; sums of several allocations are still 256-bit.
+pub const MAX_CONFIDENTIAL_AMOUNT: u64 = u64::MAX;
+
+/// Converts an amount for a confidential allocation, rejecting amounts above
+/// [`MAX_CONFIDENTIAL_AMOUNT`] instead of truncating them.
+pub fn confidential_amount(amount: Amount256) -> Result<u64, Error> {
+    let value = amount.value();
+    if value > U256::from(MAX_CONFIDENTIAL_AMOUNT) {
+        return Err(Error::AmountOverflow);
+    }
+    Ok(value.low_u64())
+}
+
+fn secret(blinding: BlindingFactor) -> Result<SecretKey, Error> {
+    SecretKey::from_slice(blinding.as_ref()).map_err(|_| Error::InvalidProof)
+}
+
+fn add(sum: Option<SecretKey>, blinding: SecretKey) -> Result<Option<SecretKey>, Error> {
+    match sum {
+        None => Ok(Some(blinding)),
+        Some(sum) => sum
+            .add_tweak(&Scalar::from(blinding))
+            .map(Some)
+            .map_err(|_| Error::InvalidProof),
+    }
+}
+
+fn checked_total(amounts: impl IntoIterator<Item = u64>) -> Result<Amount256, Error> {
+    checked_sum(amounts.into_iter().map(Amount256::from))
+}
+
+/// Blinds transfer outputs so that their commitments sum to the commitments
+/// of the spent inputs, which is what the `PcVs` check of
+/// [`crate::nia_confidential_schema`] verifies. Every output but the last
+/// gets a random blinding factor; the last one balances the equation.
+/// Amounts above [`MAX_CONFIDENTIAL_AMOUNT`] fail with
+/// [`Error::AmountOverflow`].
+pub fn blind_outputs(
+    inputs: &[RevealedValue],
+    amounts: &[Amount256],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<RevealedValue>, Error> {
+    let amounts = amounts
+        .iter()
+        .map(|amount| confidential_amount(*amount))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (last, rest) = amounts.split_last().ok_or(Error::NonEqualAmounts)?;
+    let spent = checked_total(inputs.iter().map(|input| input.value.as_u64()))?;
+    if spent != checked_total(amounts.iter().copied())? {
+        return Err(Error::NonEqualAmounts);
+    }
+
+    let mut inputs_sum = None;
+    for input in inputs {
+        inputs_sum = add(inputs_sum, secret(input.blinding)?)?;
+    }
+    let mut outputs = Vec::with_capacity(amounts.len());
+    let mut outputs_sum = None;
+    for amount in rest {
+        let blinding = SecretKey::new(rng);
+        outputs_sum = add(outputs_sum, blinding)?;
+        outputs.push(RevealedValue::with(*amount, BlindingFactor::from(blinding)));
+    }
+    let last_blinding = match (inputs_sum, outputs_sum) {
+        (Some(inputs), Some(outputs)) => add(Some(inputs), outputs.negate())?,
+        (inputs, None) => inputs,
+        (None, Some(_)) => None,
+    }
+    .ok_or(Error::InvalidProof)?;
+    outputs.push(RevealedValue::with(*last, BlindingFactor::from(last_blinding)));
+    Ok(outputs)
+}
+
+/// Holder-side `balance_of`. Consignment readers only see the commitments;
+/// the holder sums the allocations it can open with the revealed values
+/// (amount and blinding factor) it received, skipping everything else.
+pub fn balance_of(
+    allocations: impl IntoIterator<Item = ConcealedValue>,
+    secrets: &[RevealedValue],
+) -> Result<Amount256, Error> {
+    let commitments = allocations
+        .into_iter()
+        .map(|allocation| allocation.commitment)
+        .collect::<BTreeSet<_>>();
+    checked_total(
+        secrets
+            .iter()
+            .filter(|secret| commitments.contains(&secret.conceal().commitment))
+            .map(|secret| secret.value.as_u64()),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn revealed(amounts: &[u64], rng: &mut StdRng) -> Vec<RevealedValue> {
+        amounts
+            .iter()
+            .map(|amount| RevealedValue::with(*amount, BlindingFactor::from(SecretKey::new(rng))))
+            .collect()
+    }
+
+    fn blinding_sum(values: &[RevealedValue]) -> Option<SecretKey> {
+        values.iter().fold(None, |sum, value| add(sum, secret(value.blinding).unwrap()).unwrap())
+    }
+
+    #[test]
+    fn blinding_balances() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let inputs = revealed(&[60, 40], &mut rng);
+        let amounts = [Amount256::from(25u64), Amount256::from(70u64), Amount256::from(5u64)];
+        let outputs = blind_outputs(&inputs, &amounts, &mut rng).unwrap();
+        let values = outputs.iter().map(|output| output.value.as_u64()).collect::<Vec<_>>();
+        assert_eq!(values, vec![25, 70, 5]);
+        assert_eq!(blinding_sum(&outputs), blinding_sum(&inputs));
+
+        // the same seed blinds the same way
+        let mut rng = StdRng::seed_from_u64(7);
+        let again = revealed(&[60, 40], &mut rng);
+        assert_eq!(again, inputs);
+        assert_eq!(blind_outputs(&again, &amounts, &mut rng), Ok(outputs));
+    }
+
+    #[test]
+    fn single_output() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let inputs = revealed(&[10, 20], &mut rng);
+        let outputs = blind_outputs(&inputs, &[Amount256::from(30u64)], &mut rng).unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(blinding_sum(&outputs), blinding_sum(&inputs));
+    }
+
+    #[test]
+    fn unbalanced() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let inputs = revealed(&[10], &mut rng);
+        assert_eq!(
+            blind_outputs(&inputs, &[Amount256::from(11u64)], &mut rng),
+            Err(Error::NonEqualAmounts)
+        );
+        assert_eq!(blind_outputs(&inputs, &[], &mut rng), Err(Error::NonEqualAmounts));
+        let above = Amount256::with(U256::from(MAX_CONFIDENTIAL_AMOUNT) + U256::from(1u64));
+        assert_eq!(blind_outputs(&inputs, &[above], &mut rng), Err(Error::AmountOverflow));
+    }
+}
@@ -0,0 +1,220 @@
+//RGB-E20 allocation consolidation
+use std::collections::BTreeSet;
+
+use bp::Outpoint;
+use rgbstd::contract::{ContractId, GraphSeal};
+use rgbstd::persistence::{Inventory, Stock};
+use rgbstd::Transition;
+
+use crate::rgb_alu::MAX_U256_STATES;
+use crate::rgb_e20_interface::{checked_sum, rgb_e20, Amount256, Error};
+use crate::rgb_e20_selection::{groups, Holding};
+use crate::rgb_e20_transfer::TransferError;
+
+/// Default cap on the bitcoin inputs of a single consolidation, keeping the
+/// witness transaction well below standardness limits.
+pub const DEFAULT_MAX_INPUTS: usize = 32;
+
+// Weight units of a version 2 segwit transaction spending taproot key-path
+// inputs into a single taproot output carrying the commitment.
+const TX_OVERHEAD_WU: u64 = 42;
+const TAPROOT_INPUT_WU: u64 = 230;
+const TAPROOT_OUTPUT_WU: u64 = 172;
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ConsolidateError {
+    /// allocations sit on fewer than two bitcoin outputs; nothing to merge.
+    NothingToConsolidate,
+    /// max inputs must allow at least two bitcoin inputs.
+    MaxInputsTooLow,
+    /// invalid consolidation: {0:?}.
+    #[from]
+    Contract(Error),
+    /// {0}
+    #[from]
+    Transfer(TransferError),
+}
+
+/// Estimated fee in satoshis of a transaction with `inputs` taproot inputs
+/// and `outputs` taproot outputs at `fee_rate` sat/vbyte.
+pub fn estimate_fee(inputs: usize, outputs: usize, fee_rate: u64) -> u64 {
+    let weight = TX_OVERHEAD_WU
+        + TAPROOT_INPUT_WU * inputs as u64
+        + TAPROOT_OUTPUT_WU * outputs as u64;
+    weight.div_ceil(4) * fee_rate
+}
+
+/// Transfer-to-self merging many small allocations into one seal.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Consolidation {
+    pub spent: Vec<Holding>,
+    pub amount: Amount256,
+    pub seal: GraphSeal,
+    /// Bitcoin outputs the witness transaction must spend.
+    pub bitcoin_inputs: BTreeSet<Outpoint>,
+    /// Estimated witness transaction fee in satoshis.
+    pub fee: u64,
+}
+
+impl Consolidation {
+    /// Plans merging the smallest holdings into `seal`, spending at most
+    /// `max_inputs` bitcoin outputs and at most [`MAX_U256_STATES`]
+    /// allocations, the most the schema sums in a transfer. Allocations on the
+    /// same output are always merged together, since spending the output
+    /// spends all of them; outputs whose allocations don't fit are skipped.
+    pub fn plan(
+        holdings: impl IntoIterator<Item = Holding>,
+        seal: GraphSeal,
+        max_inputs: usize,
+        fee_rate: u64,
+    ) -> Result<Self, ConsolidateError> {
+        if max_inputs < 2 {
+            return Err(ConsolidateError::MaxInputsTooLow);
+        }
+        let mut groups = groups(holdings)?;
+        if groups.len() < 2 {
+            return Err(ConsolidateError::NothingToConsolidate);
+        }
+        groups.sort_by_key(|group| group.amount.value());
+        let mut picked = Vec::with_capacity(max_inputs.min(groups.len()));
+        let mut allocations = 0usize;
+        for group in groups {
+            if picked.len() == max_inputs {
+                break;
+            }
+            if allocations + group.holdings.len() > MAX_U256_STATES as usize {
+                continue;
+            }
+            allocations += group.holdings.len();
+            picked.push(group);
+        }
+        if picked.len() < 2 {
+            return Err(ConsolidateError::NothingToConsolidate);
+        }
+        let groups = picked;
+
+        let spent = groups
+            .iter()
+            .flat_map(|group| group.holdings.iter().copied())
+            .collect::<Vec<_>>();
+        let bitcoin_inputs = groups.iter().map(|group| group.outpoint).collect::<BTreeSet<_>>();
+        Ok(Consolidation {
+            amount: checked_sum(spent.iter().map(|holding| holding.amount))?,
+            fee: estimate_fee(bitcoin_inputs.len(), 1, fee_rate),
+            spent,
+            seal,
+            bitcoin_inputs,
+        })
+    }
+
+    pub fn compose(
+        &self,
+        contract_id: ContractId,
+        stock: &mut Stock,
+    ) -> Result<Transition, ConsolidateError> {
+        let mut builder = stock
+            .transition_builder(contract_id, rgb_e20().name, Some(tn!("Transfer")))
+            .map_err(|e| TransferError::Stash(e.to_string()))?;
+        for holding in &self.spent {
+            builder = builder
+                .add_input(holding.opout)
+                .map_err(|e| TransferError::Stash(e.to_string()))?;
+        }
+        let transition = builder
+            .add_data_state("beneficiary", self.seal, self.amount)
+            .map_err(|e| TransferError::Stash(e.to_string()))?
+            .complete_transition(contract_id)
+            .map_err(|e| TransferError::Stash(e.to_string()))?;
+        Ok(transition)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rgbstd::contract::{OpId, Opout};
+
+    use super::*;
+
+    fn outpoint(n: u8) -> Outpoint {
+        format!("{}:0", format!("{n:02x}").repeat(32)).parse().unwrap()
+    }
+
+    fn seal() -> GraphSeal { GraphSeal::from(outpoint(0xff)) }
+
+    /// Holdings numbered from 1, each given as its outpoint number and amount.
+    fn holdings(allocations: &[(u8, u64)]) -> Vec<Holding> {
+        allocations
+            .iter()
+            .zip(1u8..)
+            .map(|((outpoint_no, amount), n)| Holding {
+                opout: Opout::new(OpId::from([n; 32]), 20000, 0),
+                outpoint: outpoint(*outpoint_no),
+                amount: Amount256::from(*amount),
+            })
+            .collect()
+    }
+
+    fn outpoints(plan: &Consolidation) -> Vec<Outpoint> {
+        plan.bitcoin_inputs.iter().copied().collect()
+    }
+
+    #[test]
+    fn fee() {
+        assert_eq!(estimate_fee(1, 1, 1), 111);
+        // 674 WU round up to 169 vbytes
+        assert_eq!(estimate_fee(2, 1, 3), 507);
+        assert_eq!(estimate_fee(2, 1, 0), 0);
+    }
+
+    #[test]
+    fn smallest_first() {
+        let holdings = holdings(&[(1, 50), (2, 10), (3, 40), (4, 20), (5, 30)]);
+        let plan = Consolidation::plan(holdings, seal(), 3, 2).unwrap();
+        assert_eq!(outpoints(&plan), vec![outpoint(2), outpoint(4), outpoint(5)]);
+        assert_eq!(plan.amount, Amount256::from(60u64));
+        assert_eq!(plan.fee, estimate_fee(3, 1, 2));
+    }
+
+    #[test]
+    fn same_outpoint() {
+        let holdings = holdings(&[(1, 5), (2, 100), (1, 7)]);
+        let plan = Consolidation::plan(holdings, seal(), 2, 1).unwrap();
+        assert_eq!(plan.spent.len(), 3);
+        assert_eq!(outpoints(&plan), vec![outpoint(1), outpoint(2)]);
+        assert_eq!(plan.amount, Amount256::from(112u64));
+    }
+
+    #[test]
+    fn allocation_limit() {
+        // ten allocations on output 1, one on output 2, ten on output 3
+        let mut allocations = vec![(1, 1); 10];
+        allocations.push((2, 20));
+        allocations.extend(vec![(3, 3); 10]);
+        let plan = Consolidation::plan(holdings(&allocations), seal(), DEFAULT_MAX_INPUTS, 1);
+        let plan = plan.unwrap();
+        assert_eq!(plan.spent.len(), 11);
+        assert_eq!(outpoints(&plan), vec![outpoint(1), outpoint(2)]);
+        assert_eq!(plan.amount, Amount256::from(30u64));
+    }
+
+    #[test]
+    fn errors() {
+        let single = holdings(&[(1, 5), (1, 7)]);
+        assert_eq!(
+            Consolidation::plan(single, seal(), DEFAULT_MAX_INPUTS, 1),
+            Err(ConsolidateError::NothingToConsolidate)
+        );
+        // the only other output holds more allocations than a transfer can spend
+        let mut allocations = vec![(1, 1); MAX_U256_STATES as usize + 1];
+        allocations.push((2, 1));
+        assert_eq!(
+            Consolidation::plan(holdings(&allocations), seal(), DEFAULT_MAX_INPUTS, 1),
+            Err(ConsolidateError::NothingToConsolidate)
+        );
+        assert_eq!(
+            Consolidation::plan(holdings(&[(1, 5), (2, 7)]), seal(), 1, 1),
+            Err(ConsolidateError::MaxInputsTooLow)
+        );
+    }
+}
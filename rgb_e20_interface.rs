@@ -1,14 +1,17 @@
 //RGB-E20
 use amplify::confinement::LargeVec;
 use bp::bc::stl::bitcoin_stl;
+use strict_types::stl::std_stl;
 use strict_types::{CompileError, LibBuilder, TypeLib};
 
 use super::{
     AssignIface, GenesisIface, GlobalIface, Iface, OwnedIface, Req, TransitionIface, VerNo,
 };
 use crate::interface::contract::OutpointFilter;
+use crate::interface::rgb_ownable_stl;
 use crate::interface::{ArgSpec, ContractIface, FungibleAllocation};
-use crate::stl::{Amount, ContractData, DivisibleAssetSpec, StandardTypes};
+use crate::rgb_abi::{self, AbiError, Selector, Token};
+use crate::stl::{rgb_contract_stl, Amount, ContractData, DivisibleAssetSpec, StandardTypes};
 use std::collections::HashMap;
 
 pub const LIB_NAME_RGB_E20: &str = "RGB-E20";
@@ -19,6 +22,12 @@ const INVALID_PROOF: u8 = 3;
 const INSUFFICIENT_RESERVES: u8 = 4;
 const INSUFFICIENT_COVERAGE: u8 = 5;
 const ISSUE_EXCEEDS_ALLOWANCE: u8 = 6;
+const INVALID_SIGNATURE: u8 = 7;
+const PERMIT_EXPIRED: u8 = 8;
+const INVALID_NONCE: u8 = 9;
+const PAUSED: u8 = 10;
+const UNAUTHORIZED: u8 = 11;
+const AMOUNT_OVERFLOW: u8 = 12;
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
@@ -32,10 +41,134 @@ pub enum Error {
     InsufficientReserves = INSUFFICIENT_RESERVES,
     InsufficientCoverage = INSUFFICIENT_COVERAGE,
     IssueExceedsAllowance = ISSUE_EXCEEDS_ALLOWANCE,
+    InvalidSignature = INVALID_SIGNATURE,
+    PermitExpired = PERMIT_EXPIRED,
+    InvalidNonce = INVALID_NONCE,
+    Paused = PAUSED,
+    Unauthorized = UNAUTHORIZED,
+    AmountOverflow = AMOUNT_OVERFLOW,
 }
 
-type Balances = HashMap<Principal, u64>;
-type Allowances = HashMap<Principal, HashMap<Principal, u64>>;
+/// 256-bit token amount matching ERC-20 `uint256`. Kept as little-endian
+/// bytes, so AluVM loads it into an `a256` register without conversion.
+#[derive(Wrapper, Copy, Clone, Eq, PartialEq, Hash, Debug, Default, From)]
+#[wrapper(Deref)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E20)]
+pub struct Amount256([u8; 32]);
+
+impl Amount256 {
+    pub const ZERO: Amount256 = Amount256([0u8; 32]);
+
+    pub fn with(value: U256) -> Self { Amount256(value.to_le_bytes()) }
+
+    pub fn value(self) -> U256 { U256::from_le_bytes(self.0) }
+
+    pub fn checked_add(self, other: Self) -> Result<Self, Error> {
+        self.value()
+            .checked_add(other.value())
+            .map(Amount256::with)
+            .ok_or(Error::AmountOverflow)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Result<Self, Error> {
+        self.value()
+            .checked_sub(other.value())
+            .map(Amount256::with)
+            .ok_or(Error::InsufficientCoverage)
+    }
+}
+
+impl From<U256> for Amount256 {
+    fn from(value: U256) -> Self { Amount256::with(value) }
+}
+
+impl From<u64> for Amount256 {
+    fn from(value: u64) -> Self { Amount256::with(U256::from(value)) }
+}
+
+impl From<Amount256> for U256 {
+    fn from(amount: Amount256) -> Self { amount.value() }
+}
+
+/// Sums amounts, failing instead of wrapping around 2^256.
+pub fn checked_sum(amounts: impl IntoIterator<Item = Amount256>) -> Result<Amount256, Error> {
+    amounts.into_iter().try_fold(Amount256::ZERO, Amount256::checked_add)
+}
+
+/// Client-side counterpart of the schema conservation check: inputs and
+/// outputs of a transfer must sum to the same amount.
+pub fn conserves(
+    inputs: impl IntoIterator<Item = Amount256>,
+    outputs: impl IntoIterator<Item = Amount256>,
+) -> Result<(), Error> {
+    if checked_sum(inputs)? != checked_sum(outputs)? {
+        return Err(Error::NonEqualAmounts);
+    }
+    Ok(())
+}
+
+/// Client-side counterpart of the schema burn check: outputs of a burn may
+/// not sum to more than its inputs. Returns the burned amount.
+pub fn burned(
+    inputs: impl IntoIterator<Item = Amount256>,
+    outputs: impl IntoIterator<Item = Amount256>,
+) -> Result<Amount256, Error> {
+    checked_sum(inputs)?.checked_sub(checked_sum(outputs)?)
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E20)]
+pub struct Allowance {
+    pub owner: Account,
+    pub spender: Account,
+    pub value: Amount256,
+}
+
+/// Latest permit nonce used by an owner, kept on the owner's seal and
+/// advanced by each `Permit` transition spending it.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E20)]
+pub struct PermitNonce {
+    // leads the value so the schema script reads it at a fixed offset
+    pub nonce: u64,
+    pub owner: Account,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E20)]
+pub struct PermitSignature {
+    pub v: u8,
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+}
+
+/// Signed part of an EIP-2612 permit recorded by the `Permit` transition.
+/// Together with the allowance and the nonce it assigns, it lets anyone
+/// check the owner's signature from the contract history.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E20)]
+pub struct PermitRecord {
+    pub deadline: u64,
+    pub signature: PermitSignature,
+}
+
+/// Point in contract history at which balances are frozen for dividends or
+/// votes, committed by the issuer in a `Snapshot` transition.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E20)]
+pub struct Snapshot {
+    pub id: u64,
+    pub height: u32,
+}
+
+type Balances = HashMap<Principal, U256>;
+type Allowances = HashMap<Principal, HashMap<Principal, U256>>;
 
 #[derive(Wrapper, WrapperMut, Clone, Eq, PartialEq, Debug)]
 #[wrapper(Deref)]
@@ -47,15 +180,47 @@ pub struct RgbE20 {
     total_supply: U256,
 }
 
+fn _rgb_e20_stl() -> Result<TypeLib, CompileError> {
+    LibBuilder::new(libname!(LIB_NAME_RGB_E20), tiny_bset! {
+        std_stl().to_dependency(),
+        bitcoin_stl().to_dependency(),
+        rgb_contract_stl().to_dependency(),
+        rgb_ownable_stl().to_dependency()
+    })
+    .transpile::<Amount256>()
+    .transpile::<Allowance>()
+    .transpile::<PermitNonce>()
+    .transpile::<PermitSignature>()
+    .transpile::<PermitRecord>()
+    .transpile::<Snapshot>()
+    .transpile::<Error>()
+    .compile()
+}
+
+pub fn rgb_e20_stl() -> TypeLib { _rgb_e20_stl().expect("invalid strict type RGB-E20 library") }
+
 pub fn rgb_e20() -> Iface {
+    let types = StandardTypes::with(rgb_e20_stl());
+
     Iface {
         version: VerNo::V1,
         name: tn!("RGB-E20"),
         global_state: tiny_bmap! {
             fname!("name") => GlobalIface::required(types.get("RGBContract.Name")),
             fname!("Symbol") => GlobalIface::required(types.get("RGBContract.Data")),
-            fname!("total_supply") => GlobalIface::required(types.get("RGBContract.Amount")),
-            fname!("decimals") => GlobalIface::one_or_many(types.get("RGBContract.Amount")),
+            fname!("total_supply") => GlobalIface::required(types.get("RGB-E20.Amount256")),
+            fname!("decimals") => GlobalIface::one_or_many(types.get("RGBContract.Precision")),
+            fname!("permits") => GlobalIface::none_or_many(types.get("RGB-E20.PermitRecord")),
+            fname!("paused") => GlobalIface::required(types.get("Std.Bool")),
+            fname!("snapshots") => GlobalIface::none_or_many(types.get("RGB-E20.Snapshot")),
+        },
+        assignments: tiny_bmap! {
+            fname!("allowance") => AssignIface::public(OwnedIface::Data(types.get("RGB-E20.Allowance")), Req::NoneOrMore),
+            fname!("permit_nonce") => AssignIface::private(OwnedIface::Data(types.get("RGB-E20.PermitNonce")), Req::NoneOrMore),
+            fname!("admin_role") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
+            fname!("minter_role") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
+            fname!("burner_role") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
+            fname!("pauser_role") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
         },
         genesis: GenesisIface {
             metadata: Some(types.get("RGBContract")),
@@ -79,7 +244,24 @@ pub fn rgb_e20() -> Iface {
                 },
                 valencies: none!(),
                 errors: tiny_bset! {
-                    NON_EQUAL_AMOUNTS
+                    NON_EQUAL_AMOUNTS,
+                    PAUSED,
+                    AMOUNT_OVERFLOW
+                },
+                default_assignment: Some(fname!("beneficiary")),
+            },
+            tn!("BatchTransfer") => TransitionIface {
+                optional: true,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_non_empty("beneficiary"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    NON_EQUAL_AMOUNTS,
+                    PAUSED,
+                    AMOUNT_OVERFLOW
                 },
                 default_assignment: Some(fname!("beneficiary")),
             },
@@ -88,14 +270,15 @@ pub fn rgb_e20() -> Iface {
                 metadata: None,
                 globals: none!(),
                 inputs: tiny_bmap! {
-                    fname!("used") => ArgSpec::from_non_empty("inflationAllowance"),
+                    fname!("used") => ArgSpec::from_required("minter_role"),
                 },
                 valencies: none!(),
                 errors: tiny_bset! {
                     SUPPLY_MISMATCH,
                     INVALID_PROOF,
                     ISSUE_EXCEEDS_ALLOWANCE,
-                    INSUFFICIENT_RESERVES
+                    INSUFFICIENT_RESERVES,
+                    UNAUTHORIZED
                 },
                 default_assignment: Some(fname!("beneficiary")),
             },
@@ -108,7 +291,11 @@ pub fn rgb_e20() -> Iface {
                     fname!("to") => ArgSpec::from_required("account"),
                     fname!("value") => ArgSpec::from_required("amount"),
                 },
-                errors: none!(),
+                errors: tiny_bset! {
+                    NON_EQUAL_AMOUNTS,
+                    PAUSED,
+                    AMOUNT_OVERFLOW
+                },
                 default_assignment: Some(fname!("transferFrom")),
             },
             tn!("Burn") => TransitionIface {
@@ -118,12 +305,13 @@ pub fn rgb_e20() -> Iface {
                     fname!("burnedSupply") => ArgSpec::required(),
                 },
                 inputs: tiny_bmap! {
-                    fname!("used") => ArgSpec::from_required("burnRight"),
+                    fname!("used") => ArgSpec::from_required("burner_role"),
                 },
                 errors: tiny_bset! {
                     SUPPLY_MISMATCH,
                     INVALID_PROOF,
-                    INSUFFICIENT_COVERAGE
+                    INSUFFICIENT_COVERAGE,
+                    UNAUTHORIZED
                 },
                 default_assignment: None,
             },
@@ -145,12 +333,14 @@ pub fn rgb_e20() -> Iface {
                 metadata: None,
                 globals: none!()
                 inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("minter_role"),
                     fname!("to") => ArgSpec::from_required("account"),
                     fname!("value") => ArgSpec::from_required("amount"),
                 },
                 errors: tiny_bset! {
                     INVALID_PROOF,
-                    INSUFFICIENT_COVERAGE
+                    INSUFFICIENT_COVERAGE,
+                    UNAUTHORIZED
                 },
                 default_assignment: None,
             },
@@ -181,6 +371,76 @@ pub fn rgb_e20() -> Iface {
                 },
                 default_assignment: None,
             },
+            tn!("Permit") => TransitionIface {
+                optional: true,
+                metadata: None,
+                globals: tiny_bmap! {
+                    fname!("permits") => ArgSpec::required(),
+                },
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_non_empty("beneficiary"),
+                    fname!("nonce") => ArgSpec::from_optional("permit_nonce"),
+                    fname!("owner") => ArgSpec::from_required("account"),
+                    fname!("spender") => ArgSpec::from_required("account"),
+                    fname!("value") => ArgSpec::from_required("amount"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    NON_EQUAL_AMOUNTS,
+                    INVALID_SIGNATURE,
+                    PERMIT_EXPIRED,
+                    INVALID_NONCE,
+                    PAUSED
+                },
+                default_assignment: Some(fname!("allowance")),
+            },
+            tn!("Pause") => TransitionIface {
+                optional: true,
+                metadata: None,
+                globals: tiny_bmap! {
+                    fname!("paused") => ArgSpec::required(),
+                },
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("pauser_role"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    INVALID_PROOF,
+                    UNAUTHORIZED
+                },
+                default_assignment: Some(fname!("pauser_role")),
+            },
+            tn!("Unpause") => TransitionIface {
+                optional: true,
+                metadata: None,
+                globals: tiny_bmap! {
+                    fname!("paused") => ArgSpec::required(),
+                },
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("pauser_role"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    INVALID_PROOF,
+                    UNAUTHORIZED
+                },
+                default_assignment: Some(fname!("pauser_role")),
+            },
+            tn!("Snapshot") => TransitionIface {
+                optional: true,
+                metadata: None,
+                globals: tiny_bmap! {
+                    fname!("snapshots") => ArgSpec::required(),
+                },
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("admin_role"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    UNAUTHORIZED
+                },
+                default_assignment: Some(fname!("admin_role")),
+            },
         },
         extensions: none!(),
         error_type: types.get("RGB-E20.Error"),
@@ -192,16 +452,185 @@ pub trait RgbE20Iface {
     //Genesis
     fn init(&self, name: &str, symbol: &str);
     //Transaction
-    fn transfer(to: Account, value: u64) -> bool;
-    fn transfer_from(from: Account, to: Account, value: u64) -> bool;
-    fn approve(spender: Account, value: u64) -> bool;
-    fn mint(to: Account, value: u64) -> bool;
-    fn burn(from: Account, value: u64) -> bool;
-    fn balance_of(account: Account) -> u64;
-    fn allowance(owner: Account, spender: Account) -> u64;
+    fn transfer(to: Account, value: U256) -> bool;
+    fn transfer_batch(recipients: &[(Account, U256)]) -> bool;
+    fn transfer_from(from: Account, to: Account, value: U256) -> bool;
+    fn approve(spender: Account, value: U256) -> bool;
+    fn mint(to: Account, value: U256) -> bool;
+    fn burn(from: Account, value: U256) -> bool;
+    fn balance_of(account: Account) -> U256;
+    fn balance_of_confirmed(account: Account, min_depth: u32) -> U256;
+    fn balance_of_at(account: Account, height: u32) -> U256;
+    fn allowance(owner: Account, spender: Account) -> U256;
+    fn permit(
+        owner: Account,
+        spender: Account,
+        value: U256,
+        deadline: u64,
+        signature: PermitSignature,
+    ) -> bool;
+    fn nonces(owner: Account) -> u64;
     //Global
     fn name() -> String;
     fn symbol() -> String;
-    fn decimals() -> u64;
-    fn total_supply() -> u64;
+    fn decimals() -> u8;
+    fn total_supply() -> U256;
+    fn total_supply_at(height: u32) -> U256;
+    fn paused() -> bool;
+    //Snapshot
+    fn snapshot(height: u32) -> u64;
+    //Pausable
+    fn pause() -> bool;
+    fn unpause() -> bool;
+    //Introspection
+    fn supports_interface(interface_id: [u8; 4]) -> bool;
+}
+
+pub const SEL_TRANSFER: Selector = [0xa9, 0x05, 0x9c, 0xbb];
+pub const SEL_TRANSFER_FROM: Selector = [0x23, 0xb8, 0x72, 0xdd];
+pub const SEL_APPROVE: Selector = [0x09, 0x5e, 0xa7, 0xb3];
+pub const SEL_BALANCE_OF: Selector = [0x70, 0xa0, 0x82, 0x31];
+pub const SEL_ALLOWANCE: Selector = [0xdd, 0x62, 0xed, 0x3e];
+pub const SEL_TOTAL_SUPPLY: Selector = [0x18, 0x16, 0x0d, 0xdd];
+
+/// ERC-20 calls as ABI-encoded by Ethereum tooling. Amounts are full
+/// `uint256` words.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum RgbE20Call {
+    Transfer { to: Account, value: U256 },
+    TransferFrom { from: Account, to: Account, value: U256 },
+    Approve { spender: Account, value: U256 },
+    BalanceOf { account: Account },
+    Allowance { owner: Account, spender: Account },
+    TotalSupply,
+}
+
+impl RgbE20Call {
+    pub fn selector(&self) -> Selector {
+        match self {
+            RgbE20Call::Transfer { .. } => SEL_TRANSFER,
+            RgbE20Call::TransferFrom { .. } => SEL_TRANSFER_FROM,
+            RgbE20Call::Approve { .. } => SEL_APPROVE,
+            RgbE20Call::BalanceOf { .. } => SEL_BALANCE_OF,
+            RgbE20Call::Allowance { .. } => SEL_ALLOWANCE,
+            RgbE20Call::TotalSupply => SEL_TOTAL_SUPPLY,
+        }
+    }
+
+    /// Name of the [`rgb_e20`] operation the call maps to.
+    pub fn operation(&self) -> &'static str {
+        match self {
+            RgbE20Call::Transfer { .. } => "Transfer",
+            RgbE20Call::TransferFrom { .. } => "TransferFrom",
+            RgbE20Call::Approve { .. } => "Approve",
+            RgbE20Call::BalanceOf { .. } => "BalanceOf",
+            RgbE20Call::Allowance { .. } => "Allowance",
+            RgbE20Call::TotalSupply => "TotalSupply",
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let tokens = match self {
+            RgbE20Call::Transfer { to, value } => vec![Token::address(to), Token::u256(*value)],
+            RgbE20Call::TransferFrom { from, to, value } => {
+                vec![Token::address(from), Token::address(to), Token::u256(*value)]
+            }
+            RgbE20Call::Approve { spender, value } => {
+                vec![Token::address(spender), Token::u256(*value)]
+            }
+            RgbE20Call::BalanceOf { account } => vec![Token::address(account)],
+            RgbE20Call::Allowance { owner, spender } => {
+                vec![Token::address(owner), Token::address(spender)]
+            }
+            RgbE20Call::TotalSupply => vec![],
+        };
+        rgb_abi::encode_call(self.selector(), &tokens)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, AbiError> {
+        let (selector, mut r) = rgb_abi::split_selector(data)?;
+        let call = match selector {
+            SEL_TRANSFER => RgbE20Call::Transfer {
+                to: r.address()?,
+                value: r.u256()?,
+            },
+            SEL_TRANSFER_FROM => RgbE20Call::TransferFrom {
+                from: r.address()?,
+                to: r.address()?,
+                value: r.u256()?,
+            },
+            SEL_APPROVE => RgbE20Call::Approve {
+                spender: r.address()?,
+                value: r.u256()?,
+            },
+            SEL_BALANCE_OF => RgbE20Call::BalanceOf { account: r.address()? },
+            SEL_ALLOWANCE => RgbE20Call::Allowance {
+                owner: r.address()?,
+                spender: r.address()?,
+            },
+            SEL_TOTAL_SUPPLY => RgbE20Call::TotalSupply,
+            unknown => return Err(AbiError::UnknownSelector(unknown)),
+        };
+        Ok(call)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn above_u64() -> U256 { U256::from(u64::MAX) + U256::from(1u64) }
+
+    #[test]
+    fn amount_above_u64() {
+        let amount = Amount256::with(above_u64());
+        let mut bytes = [0u8; 32];
+        bytes[8] = 1;
+        assert_eq!(*amount, bytes);
+        assert_eq!(amount.value(), above_u64());
+        assert_eq!(Amount256::from(u64::MAX).checked_add(1u64.into()), Ok(amount));
+        assert_eq!(amount.checked_sub(1u64.into()), Ok(Amount256::from(u64::MAX)));
+    }
+
+    #[test]
+    fn sum_crosses_u64() {
+        let inputs = [Amount256::from(u64::MAX), Amount256::from(u64::MAX)];
+        let total = U256::from(u64::MAX) * U256::from(2u64);
+        assert_eq!(checked_sum(inputs).map(Amount256::value), Ok(total));
+        assert_eq!(conserves(inputs, [Amount256::with(total)]), Ok(()));
+        assert_eq!(conserves(inputs, [Amount256::from(u64::MAX)]), Err(Error::NonEqualAmounts));
+    }
+
+    #[test]
+    fn sum_overflow() {
+        let max = Amount256::with(U256::MAX);
+        assert_eq!(checked_sum([max, 1u64.into()]), Err(Error::AmountOverflow));
+        assert_eq!(conserves([max, 1u64.into()], [max]), Err(Error::AmountOverflow));
+    }
+
+    #[test]
+    fn burn_above_u64() {
+        let inputs = [Amount256::with(above_u64()), Amount256::from(1u64)];
+        assert_eq!(burned(inputs, [Amount256::from(2u64)]), Ok(Amount256::from(u64::MAX)));
+        assert_eq!(burned(inputs, []).map(Amount256::value), Ok(above_u64() + U256::from(1u64)));
+        assert_eq!(
+            burned([Amount256::from(u64::MAX)], [Amount256::with(above_u64())]),
+            Err(Error::InsufficientCoverage)
+        );
+    }
+
+    #[test]
+    fn abi_amount_above_u64() {
+        let call = RgbE20Call::Transfer {
+            to: Account::from([0x11; 20]),
+            value: above_u64(),
+        };
+        let data = call.encode();
+        assert_eq!(&data[..4], &SEL_TRANSFER);
+        // uint256 words are big-endian, so 2^64 sets byte 23 of the word
+        let mut word = [0u8; 32];
+        word[23] = 1;
+        assert_eq!(&data[36..68], &word);
+        assert_eq!(RgbE20Call::decode(&data), Ok(call));
+    }
 }
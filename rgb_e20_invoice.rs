@@ -0,0 +1,129 @@
+//RGB-E20 invoices
+use std::fmt::{self, Display, Formatter};
+
+use bp::Outpoint;
+use rgbstd::contract::ContractId;
+
+use crate::rgb_amount::{AmountError, DecimalAmount, Decimals};
+
+pub const INVOICE_SCHEME: &str = "rgb-e20:";
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum InvoiceError {
+    /// invoice does not start with `rgb-e20:`.
+    NoScheme,
+    /// invoice has no {0}.
+    Missing(&'static str),
+    /// invalid contract id {0}.
+    InvalidContract(String),
+    /// contract {0} is not known, so its decimals can't be looked up.
+    UnknownContract(ContractId),
+    /// invalid beneficiary {0}.
+    InvalidBeneficiary(String),
+    /// invalid amount: {0}
+    #[from]
+    Amount(AmountError),
+    /// invoice amount is zero.
+    ZeroAmount,
+}
+
+/// Request to pay a decimal amount of an RGB-E20 contract to a seal, in the
+/// form `rgb-e20:<contract_id>/<amount>@<txid>:<vout>`. The amount is written
+/// with the contract decimals, e.g. `1234.5678`, never in base units.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct E20Invoice {
+    pub contract_id: ContractId,
+    pub amount: DecimalAmount,
+    pub beneficiary: Outpoint,
+}
+
+impl E20Invoice {
+    /// Parses an invoice, looking up the decimals of its contract with
+    /// `decimals`. Amounts with more fractional digits than the contract
+    /// decimals are rejected.
+    pub fn parse(
+        s: &str,
+        decimals: impl FnOnce(ContractId) -> Option<Decimals>,
+    ) -> Result<Self, InvoiceError> {
+        let s = s.trim().strip_prefix(INVOICE_SCHEME).ok_or(InvoiceError::NoScheme)?;
+        let (s, beneficiary) = s.rsplit_once('@').ok_or(InvoiceError::Missing("beneficiary"))?;
+        let (contract_id, amount) = s.rsplit_once('/').ok_or(InvoiceError::Missing("amount"))?;
+        let contract_id = contract_id
+            .parse::<ContractId>()
+            .map_err(|_| InvoiceError::InvalidContract(contract_id.to_owned()))?;
+        let beneficiary = beneficiary
+            .parse::<Outpoint>()
+            .map_err(|_| InvoiceError::InvalidBeneficiary(beneficiary.to_owned()))?;
+        let decimals = decimals(contract_id).ok_or(InvoiceError::UnknownContract(contract_id))?;
+        let amount = DecimalAmount::parse(amount, decimals)?;
+        if amount.value == U256::from(0u64) {
+            return Err(InvoiceError::ZeroAmount);
+        }
+        Ok(E20Invoice {
+            contract_id,
+            amount,
+            beneficiary,
+        })
+    }
+}
+
+impl Display for E20Invoice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{INVOICE_SCHEME}{}/{}@{}", self.contract_id, self.amount, self.beneficiary)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BENEFICIARY: &str =
+        "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff000102030405060708090a0b0c0d0e0f:1";
+
+    fn contract_id() -> ContractId { ContractId::from([0x42; 32]) }
+
+    fn invoice(amount: &str) -> String {
+        format!("{INVOICE_SCHEME}{}/{amount}@{BENEFICIARY}", contract_id())
+    }
+
+    fn eighteen(id: ContractId) -> Option<Decimals> {
+        (id == contract_id()).then(|| Decimals::with(18).unwrap())
+    }
+
+    #[test]
+    fn roundtrip() {
+        let parsed = E20Invoice::parse(&invoice("1234.5678"), eighteen).unwrap();
+        assert_eq!(parsed.contract_id, contract_id());
+        assert_eq!(parsed.beneficiary, BENEFICIARY.parse().unwrap());
+        assert_eq!(
+            parsed.amount.value,
+            U256::from(12345678u64) * U256::from(100_000_000_000_000u64)
+        );
+        assert_eq!(parsed.to_string(), invoice("1234.5678"));
+    }
+
+    #[test]
+    fn excess_precision() {
+        let decimals = |_| Some(Decimals::with(2).unwrap());
+        assert_eq!(
+            E20Invoice::parse(&invoice("1.005"), decimals),
+            Err(InvoiceError::Amount(AmountError::ExcessPrecision { found: 3, max: 2 }))
+        );
+        assert!(E20Invoice::parse(&invoice("1.500"), decimals).is_ok());
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(E20Invoice::parse("rgb:abc/1@x:0", eighteen), Err(InvoiceError::NoScheme));
+        assert_eq!(
+            E20Invoice::parse(&format!("{INVOICE_SCHEME}{}/1", contract_id()), eighteen),
+            Err(InvoiceError::Missing("beneficiary"))
+        );
+        assert_eq!(E20Invoice::parse(&invoice("0.000"), eighteen), Err(InvoiceError::ZeroAmount));
+        assert_eq!(
+            E20Invoice::parse(&invoice("1"), |_| None),
+            Err(InvoiceError::UnknownContract(contract_id()))
+        );
+    }
+}
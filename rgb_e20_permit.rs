@@ -0,0 +1,174 @@
+//RGB-E20 permit (EIP-2612)
+use std::collections::HashMap;
+
+use rgbstd::contract::ContractId;
+use secp256k1::SecretKey;
+
+use crate::rgb_e20_interface::{
+    rgb_e20, Allowance, Amount256, Error, PermitNonce, PermitRecord, PermitSignature,
+};
+use crate::rgb_eip712::{
+    encode_address, encode_u256, encode_u64, Eip712Domain, Eip712Error, Eip712Signature,
+    Eip712Struct, Hash,
+};
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Permit {
+    pub owner: Account,
+    pub spender: Account,
+    pub value: U256,
+    pub nonce: u64,
+    pub deadline: u64,
+}
+
+impl Eip712Struct for Permit {
+    const ENCODED_TYPE: &'static str =
+        "Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)";
+
+    fn encode_data(&self) -> Vec<Hash> {
+        vec![
+            encode_address(&self.owner),
+            encode_address(&self.spender),
+            encode_u256(self.value),
+            encode_u64(self.nonce),
+            encode_u64(self.deadline),
+        ]
+    }
+}
+
+impl Eip712Struct for Allowance {
+    const ENCODED_TYPE: &'static str = "Allowance(address owner,address spender,uint256 value)";
+
+    fn encode_data(&self) -> Vec<Hash> {
+        vec![
+            encode_address(&self.owner),
+            encode_address(&self.spender),
+            encode_u256(self.value.value()),
+        ]
+    }
+}
+
+impl From<PermitSignature> for Eip712Signature {
+    fn from(sig: PermitSignature) -> Self {
+        Eip712Signature {
+            v: sig.v,
+            r: sig.r,
+            s: sig.s,
+        }
+    }
+}
+
+impl From<Eip712Signature> for PermitSignature {
+    fn from(sig: Eip712Signature) -> Self {
+        PermitSignature {
+            v: sig.v,
+            r: sig.r,
+            s: sig.s,
+        }
+    }
+}
+
+impl From<Eip712Error> for Error {
+    fn from(_: Eip712Error) -> Self { Error::InvalidSignature }
+}
+
+pub fn permit_domain(contract_id: ContractId) -> Eip712Domain {
+    Eip712Domain::with_contract(contract_id, &rgb_e20().name)
+}
+
+impl Permit {
+    /// Rebuilds the permit a `Permit` transition was accepted for from the
+    /// allowance, nonce and record it committed to.
+    pub fn recorded(allowance: &Allowance, nonce: &PermitNonce, record: &PermitRecord) -> Self {
+        Permit {
+            owner: allowance.owner,
+            spender: allowance.spender,
+            value: allowance.value.value(),
+            nonce: nonce.nonce,
+            deadline: record.deadline,
+        }
+    }
+
+    pub fn sign(&self, contract_id: ContractId, key: &SecretKey) -> PermitSignature {
+        permit_domain(contract_id).sign(self, key).into()
+    }
+
+    pub fn signer(
+        &self,
+        contract_id: ContractId,
+        signature: &PermitSignature,
+    ) -> Result<Account, Error> {
+        Ok(permit_domain(contract_id).recover(self, &(*signature).into())?)
+    }
+}
+
+/// Tracks the permit nonces of each owner, as kept in the `permit_nonce`
+/// state of a contract, and turns valid permits into allowance state. The
+/// schema only accepts a nonce above the one the owner spends, so each owner
+/// sees a strictly increasing sequence of their own.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PermitVerifier {
+    contract_id: ContractId,
+    next: HashMap<Account, u64>,
+}
+
+impl PermitVerifier {
+    pub fn with(
+        contract_id: ContractId,
+        nonces: impl IntoIterator<Item = PermitNonce>,
+    ) -> Result<Self, Error> {
+        let mut next = HashMap::<Account, u64>::new();
+        for PermitNonce { nonce, owner } in nonces {
+            let nonce = nonce.checked_add(1).ok_or(Error::InvalidNonce)?;
+            let entry = next.entry(owner).or_default();
+            *entry = (*entry).max(nonce);
+        }
+        Ok(PermitVerifier { contract_id, next })
+    }
+
+    /// Nonce the next permit of `owner` must be signed with.
+    pub fn nonce(&self, owner: &Account) -> u64 { self.next.get(owner).copied().unwrap_or(0) }
+
+    pub fn verify(
+        &self,
+        permit: &Permit,
+        signature: &PermitSignature,
+        now: u64,
+    ) -> Result<Allowance, Error> {
+        if permit.deadline < now {
+            return Err(Error::PermitExpired);
+        }
+        if permit.nonce != self.nonce(&permit.owner) {
+            return Err(Error::InvalidNonce);
+        }
+        permit_domain(self.contract_id).verify(permit, &(*signature).into(), &permit.owner)?;
+        Ok(Allowance {
+            owner: permit.owner,
+            spender: permit.spender,
+            value: Amount256::with(permit.value),
+        })
+    }
+
+    /// Verifies the permit and consumes its nonce, returning the allowance
+    /// and the owner's new nonce to assign, and the `permits` global entry
+    /// for the `Permit` transition.
+    pub fn accept(
+        &mut self,
+        permit: &Permit,
+        signature: &PermitSignature,
+        now: u64,
+    ) -> Result<(Allowance, PermitNonce, PermitRecord), Error> {
+        let allowance = self.verify(permit, signature, now)?;
+        let next = permit.nonce.checked_add(1).ok_or(Error::InvalidNonce)?;
+        self.next.insert(permit.owner, next);
+        let nonce = PermitNonce {
+            owner: permit.owner,
+            nonce: permit.nonce,
+        };
+        let record = PermitRecord {
+            deadline: permit.deadline,
+            signature: *signature,
+        };
+        Ok((allowance, nonce, record))
+    }
+}
@@ -1,27 +1,186 @@
 /// RGB-E20 SCHEMA
-use aluvm::library::{Lib, LibSite};
-use rgbstd::interface::{rgb_e20,IfaceImpl, NamedField, NamedType, VerNo};
+use aluvm::isa::{BytesOp, CmpOp, ControlFlowOp, Instr, PutOp};
+use aluvm::library::LibSite;
+use aluvm::reg::{Reg16, Reg32, RegA, RegR, RegS};
+use rgbstd::interface::rgb_e20::Error;
+use rgbstd::interface::{rgb_e20, rgb_e20_stl, IfaceImpl, NamedField, NamedType, VerNo};
 use rgbstd::schema::{
-    FungibleType, GenesisSchema, GlobalStateSchema, Occurrences, Schema, Script, StateSchema,
-    SubSchema, TransitionSchema,
+    FungibleType, GenesisSchema, GlobalStateSchema, Occurrences, Schema, SchemaId, Script,
+    StateSchema, SubSchema, TransitionSchema,
 };
 use rgbstd::stl::StandardTypes;
 use rgbstd::vm::{AluScript, ContractOp, EntryPoint, RgbIsa};
 use strict_types::{SemId, Ty};
 
+use crate::rgb_access_control_schema::{
+    access_control_impl, grant_role_schema, has_role_schema, renounce_role_schema, revoke_role_lib,
+    revoke_role_schema, OS_ROLE_ADMIN, OS_ROLE_BURNER, OS_ROLE_MINTER, OS_ROLE_PAUSER, ROLE_RIGHT,
+    TS_GRANT_ROLE, TS_HAS_ROLE, TS_RENOUNCE_ROLE, TS_REVOKE_ROLE,
+};
+use crate::rgb_alu::{
+    all_of, any_inputs, code_len, conserves_committed, conserves_u256, counter_advances, errno,
+    flag_unset, reduces_u256, sets_flag, unsupported, Check,
+};
+use crate::rgb_ownable_schema::{
+    ownable_impl, owner_schema, ownership_lib, renounce_ownership_schema,
+    transfer_ownership_schema, OS_OWNER_RIGHT, TS_OWNER, TS_RENOUNCE_OWNERSHIP,
+    TS_TRANSFER_OWNERSHIP,
+};
 use crate::GS_TIMESTAMP;
 
 const GS_NOMINAL: u16 = 20000;
 const GS_CONTRACT: u16 = 20001;
-const OS_ASSETS: u16 = 20000;
+const GS_PERMITS: u16 = 20002;
+const GS_PAUSED: u16 = 20003;
+const GS_SNAPSHOTS: u16 = 20004;
+pub const OS_ASSETS: u16 = 20000;
+const OS_ALLOWANCE: u16 = 20001;
+const OS_PERMIT_NONCE: u16 = 20002;
 const TS_TRANSFER: u16 = 20000;
+const TS_PERMIT: u16 = 20001;
+const TS_PAUSE: u16 = 20002;
+const TS_UNPAUSE: u16 = 20003;
+const TS_BATCH_TRANSFER: u16 = 20004;
+const TS_SNAPSHOT: u16 = 20005;
+
+/// How `OS_ASSETS` amounts are kept in the contract state.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum AmountPrivacy {
+    /// Revealed 256-bit amounts, conserved by an AluVM sum over the states.
+    Public,
+    /// 64-bit amounts hidden in Pedersen commitments with bulletproof range
+    /// proofs, conserved by the `PcVs` commitment check.
+    Confidential,
+}
+
+/// The allowance and the nonce assigned by a permit must be of the same
+/// owner, and so must the nonce it spends, if any. The owner leads
+/// `Allowance` and follows the `u64` nonce in `PermitNonce`.
+fn permit_owner() -> Check {
+    let mut check = vec![
+        errno(Error::InvalidNonce as u8),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg5, Box::new(8u16.into()))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg6, Box::new(0u16.into()))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdS(OS_PERMIT_NONCE, 0, RegS::from(0)))),
+        Instr::Bytes(BytesOp::Extr(RegS::from(0), RegR::R160, Reg16::Reg0, Reg16::Reg5)),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdS(OS_ALLOWANCE, 0, RegS::from(1)))),
+        Instr::Bytes(BytesOp::Extr(RegS::from(1), RegR::R160, Reg16::Reg1, Reg16::Reg6)),
+        Instr::Cmp(CmpOp::EqR(none!(), RegR::R160, Reg32::Reg0, Reg32::Reg1)),
+    ];
+    let jif_allowance = check.len();
+    check.push(Instr::ControlFlow(ControlFlowOp::Jif(0)));
+    check.push(Instr::ControlFlow(ControlFlowOp::Fail));
+    check[jif_allowance] = Instr::ControlFlow(ControlFlowOp::Jif(code_len(&check)));
+    check.extend([
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnP(OS_PERMIT_NONCE, Reg16::Reg0))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg4, Box::new(0u16.into()))),
+        Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg4)),
+    ]);
+    let jif_first = check.len();
+    check.push(Instr::ControlFlow(ControlFlowOp::Jif(0)));
+    check.push(Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdP(OS_PERMIT_NONCE, 0, RegS::from(2)))));
+    check.push(Instr::Bytes(BytesOp::Extr(RegS::from(2), RegR::R160, Reg16::Reg1, Reg16::Reg5)));
+    check.push(Instr::Cmp(CmpOp::EqR(none!(), RegR::R160, Reg32::Reg0, Reg32::Reg1)));
+    check[jif_first] = Instr::ControlFlow(ControlFlowOp::Jif(code_len(&check)));
+    check
+}
+
+pub fn nia_schema() -> SubSchema { nia_schema_with(AmountPrivacy::Public) }
+
+pub fn nia_confidential_schema() -> SubSchema { nia_schema_with(AmountPrivacy::Confidential) }
 
-pub fn nia_schema() -> SubSchema {
-    let types = StandardTypes::new();
+fn nia_schema_with(privacy: AmountPrivacy) -> SubSchema {
+    let types = StandardTypes::with(rgb_e20_stl());
 
-    let code = [RgbIsa::Contract(ContractOp::PcVs(OS_ASSETS))];
-    let alu_lib = Lib::assemble(&code).unwrap();
-    let alu_id = alu_lib.id();
+    // permits are signed by the owner but committed by spending the owner's
+    // assets, which authenticates the owner; the owner's nonce is spent and
+    // assigned back advanced, so each owner has their own sequence
+    let nonce = counter_advances(OS_PERMIT_NONCE, Error::InvalidNonce as u8);
+    let minter = any_inputs(&[OS_ROLE_MINTER, OS_OWNER_RIGHT], Error::Unauthorized as u8);
+    let burner = any_inputs(&[OS_ROLE_BURNER, OS_OWNER_RIGHT], Error::Unauthorized as u8);
+    let (assets, transfer_lib, permit_lib, mint_lib, burn_lib) = match privacy {
+        AmountPrivacy::Public => (
+            StateSchema::Structured(types.get("RGB-E20.Amount256")),
+            all_of([
+                conserves_u256(OS_ASSETS, Error::NonEqualAmounts as u8),
+                flag_unset(GS_PAUSED, Error::Paused as u8),
+            ]),
+            all_of([
+                conserves_u256(OS_ASSETS, Error::NonEqualAmounts as u8),
+                nonce,
+                permit_owner(),
+                flag_unset(GS_PAUSED, Error::Paused as u8),
+            ]),
+            all_of([minter]),
+            all_of([burner, reduces_u256(OS_ASSETS, Error::InsufficientCoverage as u8)]),
+        ),
+        // hidden amounts are conserved by `PcVs`, which can't tell an issued
+        // or burned excess from an unbalanced transfer, so the supply is
+        // fixed at genesis
+        AmountPrivacy::Confidential => (
+            StateSchema::Fungible(FungibleType::Unsigned64Bit),
+            all_of([
+                conserves_committed(OS_ASSETS, Error::NonEqualAmounts as u8),
+                flag_unset(GS_PAUSED, Error::Paused as u8),
+            ]),
+            all_of([
+                conserves_committed(OS_ASSETS, Error::NonEqualAmounts as u8),
+                nonce,
+                permit_owner(),
+                flag_unset(GS_PAUSED, Error::Paused as u8),
+            ]),
+            all_of([minter, unsupported(Error::InvalidProof as u8)]),
+            all_of([burner, unsupported(Error::InvalidProof as u8)]),
+        ),
+    };
+    let transfer_id = transfer_lib.id();
+    let permit_id = permit_lib.id();
+    let mint_id = mint_lib.id();
+    let burn_id = burn_lib.id();
+    let pause_lib = all_of([
+        any_inputs(&[OS_ROLE_PAUSER, OS_OWNER_RIGHT], Error::Unauthorized as u8),
+        sets_flag(GS_PAUSED, true, Error::InvalidProof as u8),
+    ]);
+    let pause_id = pause_lib.id();
+    let unpause_lib = all_of([
+        any_inputs(&[OS_ROLE_PAUSER, OS_OWNER_RIGHT], Error::Unauthorized as u8),
+        sets_flag(GS_PAUSED, false, Error::InvalidProof as u8),
+    ]);
+    let unpause_id = unpause_lib.id();
+    let snapshot_lib = all_of([any_inputs(&[OS_ROLE_ADMIN, OS_OWNER_RIGHT], Error::Unauthorized as u8)]);
+    let snapshot_id = snapshot_lib.id();
+    let revoke_role_lib = revoke_role_lib();
+    let revoke_role_id = revoke_role_lib.id();
+    let ownership_lib = ownership_lib();
+    let ownership_id = ownership_lib.id();
+    let script = AluScript {
+        libs: confined_bmap! {
+            transfer_id => transfer_lib,
+            permit_id => permit_lib,
+            mint_id => mint_lib,
+            burn_id => burn_lib,
+            pause_id => pause_lib,
+            unpause_id => unpause_lib,
+            snapshot_id => snapshot_lib,
+            revoke_role_id => revoke_role_lib,
+            ownership_id => ownership_lib,
+        },
+        entry_points: confined_bmap! {
+            EntryPoint::ValidateTransition(TS_TRANSFER) => LibSite::with(0, transfer_id),
+            EntryPoint::ValidateTransition(TS_TRANSFER_FROM) => LibSite::with(0, transfer_id),
+            EntryPoint::ValidateTransition(TS_BATCH_TRANSFER) => LibSite::with(0, transfer_id),
+            EntryPoint::ValidateTransition(TS_PERMIT) => LibSite::with(0, permit_id),
+            EntryPoint::ValidateTransition(TS_ISSUE) => LibSite::with(0, mint_id),
+            EntryPoint::ValidateTransition(TS_MINT) => LibSite::with(0, mint_id),
+            EntryPoint::ValidateTransition(TS_BURN) => LibSite::with(0, burn_id),
+            EntryPoint::ValidateTransition(TS_PAUSE) => LibSite::with(0, pause_id),
+            EntryPoint::ValidateTransition(TS_UNPAUSE) => LibSite::with(0, unpause_id),
+            EntryPoint::ValidateTransition(TS_SNAPSHOT) => LibSite::with(0, snapshot_id),
+            EntryPoint::ValidateTransition(TS_REVOKE_ROLE) => LibSite::with(0, revoke_role_id),
+            EntryPoint::ValidateTransition(TS_TRANSFER_OWNERSHIP) => LibSite::with(0, ownership_id),
+            EntryPoint::ValidateTransition(TS_RENOUNCE_OWNERSHIP) => LibSite::with(0, ownership_id),
+        },
+    };
     Schema {
         ffv: zero!(),
         subset_of: None,
@@ -29,21 +188,36 @@ pub fn nia_schema() -> SubSchema {
         global_types: tiny_bmap! {
             GS_NAME => GlobalStateSchema::once(types.get("RGBContract.Text")),
             GS_SYMBOL => GlobalStateSchema::once(types.get("RGBContract.Text")),
-            GS_DECIMALS => GlobalStateSchema::once(types.get("RGBContract.amount")),
-            GS_TOTAL_SUPPLY => GlobalStateSchema::once(types.get("RGBContract.amount")),
-
+            GS_DECIMALS => GlobalStateSchema::once(types.get("RGBContract.Precision")),
+            GS_TOTAL_SUPPLY => GlobalStateSchema::once(types.get("RGB-E20.Amount256")),
+            GS_PERMITS => GlobalStateSchema::many(types.get("RGB-E20.PermitRecord")),
+            GS_PAUSED => GlobalStateSchema::many(types.get("Std.Bool")),
+            GS_SNAPSHOTS => GlobalStateSchema::many(types.get("RGB-E20.Snapshot")),
         },
         owned_types: tiny_bmap! {
-            OS_ASSETS => StateSchema::Fungible(FungibleType::Unsigned64Bit),
+            OS_ASSETS => assets,
+            OS_ALLOWANCE => StateSchema::Structured(types.get("RGB-E20.Allowance")),
+            OS_PERMIT_NONCE => StateSchema::Structured(types.get("RGB-E20.PermitNonce")),
+            OS_ROLE_ADMIN => ROLE_RIGHT,
+            OS_ROLE_MINTER => ROLE_RIGHT,
+            OS_ROLE_BURNER => ROLE_RIGHT,
+            OS_ROLE_PAUSER => ROLE_RIGHT,
+            OS_OWNER_RIGHT => StateSchema::Structured(types.get("RGB-Ownable.OwnerRight")),
         },
         valency_types: none!(),
         genesis: GenesisSchema {
             metadata: Ty::<SemId>::UNIT.id(None),
             globals: tiny_bmap! {
                 GS_INIT => Occurrences::Once,
+                GS_PAUSED => Occurrences::Once,
             },
             assignments: tiny_bmap! {
                 OS_ASSETS => Occurrences::OnceOrMore,
+                OS_ROLE_ADMIN => Occurrences::Once,
+                OS_ROLE_MINTER => Occurrences::NoneOrMore,
+                OS_ROLE_BURNER => Occurrences::NoneOrMore,
+                OS_ROLE_PAUSER => Occurrences::NoneOrMore,
+                OS_OWNER_RIGHT => Occurrences::Once,
             },
             valencies: none!(),
         },
@@ -60,15 +234,31 @@ pub fn nia_schema() -> SubSchema {
                 },
                 valencies: none!(),
             },
-            TS_ISSUE => TransitionSchema {
+            TS_BATCH_TRANSFER => TransitionSchema {
                 metadata: Ty::<SemId>::UNIT.id(None),
                 globals: none!(),
-                inputs: none!(),
+                inputs: tiny_bmap! {
+                    OS_ASSETS => Occurrences::OnceOrMore
+                },
                 assignments: tiny_bmap! {
                     OS_ASSETS => Occurrences::OnceOrMore
                 },
                 valencies: none!(),
             },
+            TS_ISSUE => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    OS_ROLE_MINTER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_ASSETS => Occurrences::OnceOrMore,
+                    OS_ROLE_MINTER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                valencies: none!(),
+            },
             TS_TRANSFER_FROM => TransitionSchema {
                 metadata: Ty::<SemId>::UNIT.id(None),
                 globals: none!(),
@@ -83,9 +273,15 @@ pub fn nia_schema() -> SubSchema {
             TS_BURN => TransitionSchema {
                 metadata: Ty::<SemId>::UNIT.id(None),
                 globals: none!(),
-                inputs: none!(),
+                inputs: tiny_bmap! {
+                    OS_ASSETS => Occurrences::OnceOrMore,
+                    OS_ROLE_BURNER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
                 assignments: tiny_bmap! {
-                    OS_ASSETS => Occurrences::OnceOrMore
+                    OS_ASSETS => Occurrences::NoneOrMore,
+                    OS_ROLE_BURNER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
                 },
                 valencies: none!(),
             },
@@ -102,9 +298,14 @@ pub fn nia_schema() -> SubSchema {
                 metadata: Ty::<SemId>::UNIT.id(None),
                 globals: none!(),
                 inputs: tiny_bmap! {
-                    OS_ASSETS => Occurrences::OnceOrMore
+                    OS_ROLE_MINTER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_ASSETS => Occurrences::OnceOrMore,
+                    OS_ROLE_MINTER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
                 },
-                assignments: none!(),
                 valencies: none!(),
             },
             TS_BALANCE_OF => TransitionSchema {
@@ -124,24 +325,90 @@ pub fn nia_schema() -> SubSchema {
                 },
                 assignments: none!(),
                 valencies: none!(),
-            }
-        },
-        script: Script::AluVM(AluScript {
-            libs: confined_bmap! { alu_id => alu_lib },
-            entry_points: confined_bmap! {
-                EntryPoint::ValidateOwnedState(OS_ASSETS) => LibSite::with(0, alu_id)
             },
-        }),
+            TS_PERMIT => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: tiny_bmap! {
+                    GS_PERMITS => Occurrences::Once
+                },
+                inputs: tiny_bmap! {
+                    OS_ASSETS => Occurrences::OnceOrMore,
+                    OS_PERMIT_NONCE => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_ASSETS => Occurrences::OnceOrMore,
+                    OS_ALLOWANCE => Occurrences::Once,
+                    OS_PERMIT_NONCE => Occurrences::Once
+                },
+                valencies: none!(),
+            },
+            TS_PAUSE => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: tiny_bmap! {
+                    GS_PAUSED => Occurrences::Once
+                },
+                inputs: tiny_bmap! {
+                    OS_ROLE_PAUSER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_ROLE_PAUSER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                valencies: none!(),
+            },
+            TS_UNPAUSE => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: tiny_bmap! {
+                    GS_PAUSED => Occurrences::Once
+                },
+                inputs: tiny_bmap! {
+                    OS_ROLE_PAUSER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_ROLE_PAUSER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                valencies: none!(),
+            },
+            TS_SNAPSHOT => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: tiny_bmap! {
+                    GS_SNAPSHOTS => Occurrences::Once
+                },
+                inputs: tiny_bmap! {
+                    OS_ROLE_ADMIN => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_ROLE_ADMIN => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                valencies: none!(),
+            },
+            TS_GRANT_ROLE => grant_role_schema(),
+            TS_REVOKE_ROLE => revoke_role_schema(),
+            TS_RENOUNCE_ROLE => renounce_role_schema(),
+            TS_HAS_ROLE => has_role_schema(),
+            TS_TRANSFER_OWNERSHIP => transfer_ownership_schema(),
+            TS_RENOUNCE_OWNERSHIP => renounce_ownership_schema(),
+            TS_OWNER => owner_schema()
+        },
+        script: Script::AluVM(script),
     }
 }
 
-pub fn nia_rgb_e20() -> IfaceImpl {
-    let schema = nia_schema();
+pub fn nia_rgb_e20() -> IfaceImpl { nia_impl(nia_schema().schema_id()) }
+
+pub fn nia_confidential_rgb_e20() -> IfaceImpl { nia_impl(nia_confidential_schema().schema_id()) }
+
+fn nia_impl(schema_id: SchemaId) -> IfaceImpl {
     let iface = rgb_e20();
 
     IfaceImpl {
         version: VerNo::V1,
-        schema_id: schema.schema_id(),
+        schema_id,
         iface_id: iface.iface_id(),
         genesis_iface: tiny_bset! {
             NamedField::with(GS_INIT, fname!("init")),
@@ -151,13 +418,23 @@ pub fn nia_rgb_e20() -> IfaceImpl {
             NamedField::with(GS_SYMBOL, fname!("symbol")),
             NamedField::with(GS_DECIMALS, fname!("decimals")),
             NamedField::with(GS_TOTAL_SUPPLY, fname!("total_supply")),
+            NamedField::with(GS_PERMITS, fname!("permits")),
+            NamedField::with(GS_PAUSED, fname!("paused")),
+            NamedField::with(GS_SNAPSHOTS, fname!("snapshots")),
         },
         assignments: tiny_bset! {
             NamedField::with(OS_ASSETS, fname!("beneficiary")),
+            NamedField::with(OS_ALLOWANCE, fname!("allowance")),
+            NamedField::with(OS_PERMIT_NONCE, fname!("permit_nonce")),
+            NamedField::with(OS_ROLE_ADMIN, fname!("admin_role")),
+            NamedField::with(OS_ROLE_MINTER, fname!("minter_role")),
+            NamedField::with(OS_ROLE_BURNER, fname!("burner_role")),
+            NamedField::with(OS_ROLE_PAUSER, fname!("pauser_role")),
         },
         valencies: none!(),
         transitions: tiny_bset! {
             NamedType::with(TS_TRANSFER, tn!("transfer")),
+            NamedType::with(TS_BATCH_TRANSFER, tn!("batch_transfer")),
             NamedType::with(TS_ISSUE, tn!("issue")),
             NamedType::with(TS_TRANSFER_FROM, tn!("transfer_from")),
             NamedType::with(TS_BURN, tn!("burn")),
@@ -165,7 +442,15 @@ pub fn nia_rgb_e20() -> IfaceImpl {
             NamedType::with(TS_MINT, tn!("mint")),
             NamedType::with(TS_BALANCE_OF, tn!("balance_of")),
             NamedType::with(TS_ALLOWANCE, tn!("allowance")),
+            NamedType::with(TS_PERMIT, tn!("permit")),
+            NamedType::with(TS_PAUSE, tn!("pause")),
+            NamedType::with(TS_UNPAUSE, tn!("unpause")),
+            NamedType::with(TS_SNAPSHOT, tn!("snapshot")),
         },
         extensions: none!(),
     }
 }
+
+pub fn nia_access_control() -> IfaceImpl { access_control_impl(nia_schema().schema_id()) }
+
+pub fn nia_ownable() -> IfaceImpl { ownable_impl(nia_schema().schema_id()) }
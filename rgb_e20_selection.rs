@@ -0,0 +1,282 @@
+//RGB-E20 allocation selection
+use std::collections::{BTreeMap, BTreeSet};
+
+use bp::Outpoint;
+use rgbstd::contract::{ContractId, GraphSeal, Opout};
+
+use crate::rgb_e20_interface::{checked_sum, Amount256, Error};
+use crate::rgb_e20_transfer::BatchTransfer;
+
+/// An `OS_ASSETS` allocation owned by the wallet.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Holding {
+    pub opout: Opout,
+    pub outpoint: Outpoint,
+    pub amount: Amount256,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Strategy {
+    /// Spend the biggest outputs first, leaving few large allocations.
+    #[default]
+    LargestFirst,
+    /// Spend the smallest outputs first, cleaning up dust.
+    SmallestFirst,
+    /// Prefer a single output covering the amount exactly, then the smallest
+    /// single output covering it, so no two outputs get linked.
+    Privacy,
+    /// Spend as few bitcoin outputs as possible: a single output covering
+    /// the amount if there is one, otherwise the fewest outputs, keeping the
+    /// change small.
+    MinInputs,
+}
+
+/// Allocations chosen to fund a transfer.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Selection {
+    pub spent: Vec<Holding>,
+    pub change: Option<(GraphSeal, Amount256)>,
+    /// Bitcoin outputs the witness transaction must spend.
+    pub bitcoin_inputs: BTreeSet<Outpoint>,
+}
+
+impl Selection {
+    pub fn total(&self) -> Result<Amount256, Error> {
+        checked_sum(self.spent.iter().map(|holding| holding.amount))
+    }
+
+    /// Starts a transfer spending the selected allocations, with change
+    /// going to the selection change seal.
+    pub fn transfer(&self, contract_id: ContractId) -> BatchTransfer {
+        let transfer = BatchTransfer::with(
+            contract_id,
+            self.spent.iter().map(|holding| (holding.opout, holding.amount)),
+        );
+        match self.change {
+            Some((seal, _)) => transfer.change(seal),
+            None => transfer,
+        }
+    }
+}
+
+/// Allocations sitting on the same bitcoin output. Spending the output
+/// spends all of them, so they are always selected together.
+#[derive(Clone, Debug)]
+pub(crate) struct Group {
+    pub outpoint: Outpoint,
+    pub holdings: Vec<Holding>,
+    pub amount: Amount256,
+}
+
+pub(crate) fn groups(holdings: impl IntoIterator<Item = Holding>) -> Result<Vec<Group>, Error> {
+    let mut by_outpoint = BTreeMap::<Outpoint, Vec<Holding>>::new();
+    for holding in holdings {
+        by_outpoint.entry(holding.outpoint).or_default().push(holding);
+    }
+    by_outpoint
+        .into_iter()
+        .map(|(outpoint, holdings)| {
+            let amount = checked_sum(holdings.iter().map(|holding| holding.amount))?;
+            Ok(Group { outpoint, holdings, amount })
+        })
+        .collect()
+}
+
+/// Takes groups in the given order until `target` is covered.
+fn take_until(ordered: Vec<Group>, target: Amount256) -> Result<Vec<Group>, Error> {
+    let mut selected = vec![];
+    let mut total = Amount256::ZERO;
+    for group in ordered {
+        if total.value() >= target.value() {
+            break;
+        }
+        total = total.checked_add(group.amount)?;
+        selected.push(group);
+    }
+    if total.value() < target.value() {
+        return Err(Error::InsufficientCoverage);
+    }
+    Ok(selected)
+}
+
+/// Takes the smallest group covering what is left of `target`, or the
+/// largest one if none does. The `k` largest groups cover the most any `k`
+/// groups can, so this takes as few groups as possible, and the last one
+/// taken leaves the least change. `ascending` must be sorted by amount.
+fn fewest(mut ascending: Vec<Group>, target: Amount256) -> Result<Vec<Group>, Error> {
+    let mut selected = vec![];
+    let mut total = Amount256::ZERO;
+    while total.value() < target.value() {
+        let rest = target.checked_sub(total)?;
+        let covering = ascending
+            .iter()
+            .position(|group| group.amount.value() >= rest.value());
+        let group = match covering {
+            Some(pos) => ascending.remove(pos),
+            None => ascending.pop().ok_or(Error::InsufficientCoverage)?,
+        };
+        total = total.checked_add(group.amount)?;
+        selected.push(group);
+    }
+    Ok(selected)
+}
+
+/// Picks allocations worth at least `target` according to `strategy` and
+/// sends the excess to `change`. Ties are broken by outpoint, so the result
+/// depends only on the inputs.
+pub fn select(
+    holdings: impl IntoIterator<Item = Holding>,
+    target: Amount256,
+    strategy: Strategy,
+    change: GraphSeal,
+) -> Result<Selection, Error> {
+    let mut groups = groups(holdings)?;
+    // stable sorts below keep the outpoint order for equal amounts
+    let selected = match strategy {
+        Strategy::LargestFirst => {
+            groups.sort_by(|a, b| b.amount.value().cmp(&a.amount.value()));
+            take_until(groups, target)?
+        }
+        Strategy::MinInputs => {
+            groups.sort_by_key(|group| group.amount.value());
+            fewest(groups, target)?
+        }
+        Strategy::SmallestFirst => {
+            groups.sort_by_key(|group| group.amount.value());
+            take_until(groups, target)?
+        }
+        Strategy::Privacy => {
+            groups.sort_by_key(|group| group.amount.value());
+            match groups.iter().position(|group| group.amount.value() >= target.value()) {
+                Some(pos) => vec![groups.swap_remove(pos)],
+                None => {
+                    groups.reverse();
+                    take_until(groups, target)?
+                }
+            }
+        }
+    };
+
+    let spent = selected
+        .iter()
+        .flat_map(|group| group.holdings.iter().copied())
+        .collect::<Vec<_>>();
+    let bitcoin_inputs = selected.iter().map(|group| group.outpoint).collect();
+    let total = checked_sum(spent.iter().map(|holding| holding.amount))?;
+    let rest = total.checked_sub(target)?;
+    Ok(Selection {
+        spent,
+        change: (rest != Amount256::ZERO).then_some((change, rest)),
+        bitcoin_inputs,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use rgbstd::contract::OpId;
+
+    use super::*;
+
+    fn outpoint(n: u8) -> Outpoint {
+        format!("{}:0", format!("{n:02x}").repeat(32)).parse().unwrap()
+    }
+
+    fn holding(n: u8, outpoint_no: u8, amount: u64) -> Holding {
+        Holding {
+            opout: Opout::new(OpId::from([n; 32]), 20000, 0),
+            outpoint: outpoint(outpoint_no),
+            amount: Amount256::from(amount),
+        }
+    }
+
+    /// One allocation per outpoint, numbered from 1.
+    fn holdings(amounts: &[u64]) -> Vec<Holding> {
+        amounts
+            .iter()
+            .zip(1u8..)
+            .map(|(amount, n)| holding(n, n, *amount))
+            .collect()
+    }
+
+    fn change() -> GraphSeal { GraphSeal::from(outpoint(0xff)) }
+
+    fn spent(selection: &Selection) -> Vec<u64> {
+        selection.spent.iter().map(|holding| holding.amount.value().low_u64()).collect()
+    }
+
+    fn change_amount(selection: &Selection) -> Option<u64> {
+        selection.change.map(|(_, amount)| amount.value().low_u64())
+    }
+
+    #[test]
+    fn largest_first() {
+        let selection =
+            select(holdings(&[10, 25, 70, 80]), 100u64.into(), Strategy::LargestFirst, change())
+                .unwrap();
+        assert_eq!(spent(&selection), vec![80, 70]);
+        assert_eq!(change_amount(&selection), Some(50));
+    }
+
+    #[test]
+    fn smallest_first() {
+        let selection =
+            select(holdings(&[10, 25, 70, 80]), 100u64.into(), Strategy::SmallestFirst, change())
+                .unwrap();
+        assert_eq!(spent(&selection), vec![10, 25, 70]);
+        assert_eq!(change_amount(&selection), Some(5));
+    }
+
+    #[test]
+    fn min_inputs_single() {
+        let selection =
+            select(holdings(&[20, 45, 100]), 40u64.into(), Strategy::MinInputs, change()).unwrap();
+        assert_eq!(spent(&selection), vec![45]);
+        assert_eq!(change_amount(&selection), Some(5));
+        assert_eq!(selection.bitcoin_inputs, BTreeSet::from([outpoint(2)]));
+    }
+
+    #[test]
+    fn min_inputs_fewest() {
+        let selection =
+            select(holdings(&[10, 25, 70, 80]), 100u64.into(), Strategy::MinInputs, change())
+                .unwrap();
+        assert_eq!(spent(&selection), vec![80, 25]);
+        assert_eq!(change_amount(&selection), Some(5));
+
+        let selection =
+            select(holdings(&[5, 30, 30, 30, 60]), 100u64.into(), Strategy::MinInputs, change())
+                .unwrap();
+        assert_eq!(spent(&selection), vec![60, 30, 30]);
+        assert_eq!(change_amount(&selection), Some(20));
+    }
+
+    #[test]
+    fn min_inputs_counts_outpoints() {
+        // two allocations on one outpoint are a single bitcoin input
+        let holdings = vec![holding(1, 1, 30), holding(2, 1, 30), holding(3, 2, 50)];
+        let selection = select(holdings, 55u64.into(), Strategy::MinInputs, change()).unwrap();
+        assert_eq!(spent(&selection), vec![30, 30]);
+        assert_eq!(selection.bitcoin_inputs, BTreeSet::from([outpoint(1)]));
+        assert_eq!(change_amount(&selection), Some(5));
+    }
+
+    #[test]
+    fn exact_and_ties() {
+        let selection =
+            select(holdings(&[50, 50]), 50u64.into(), Strategy::MinInputs, change()).unwrap();
+        assert_eq!(selection.bitcoin_inputs, BTreeSet::from([outpoint(1)]));
+        assert_eq!(selection.change, None);
+    }
+
+    #[test]
+    fn insufficient() {
+        let strategies =
+            [Strategy::LargestFirst, Strategy::SmallestFirst, Strategy::Privacy, Strategy::MinInputs];
+        for strategy in strategies {
+            assert_eq!(
+                select(holdings(&[10, 20]), 31u64.into(), strategy, change()),
+                Err(Error::InsufficientCoverage)
+            );
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//RGB-E20 balance snapshots
+use std::collections::{BTreeMap, HashMap};
+
+use bp::Outpoint;
+use rgbstd::contract::ContractId;
+
+use crate::rgb_e20_interface::{checked_sum, Amount256, Error, Snapshot};
+use crate::rgb_history::{Direction, Entry, Ledger, Value};
+
+/// Amount moved by an entry of `contract_id` mined at or below `height`.
+/// Genesis entries count at any height; tentative ones at none.
+fn amount_at(entry: &Entry, contract_id: ContractId, height: u32) -> Option<Amount256> {
+    let mined = entry.witness.is_none() || entry.height.is_some_and(|h| h <= height);
+    if entry.contract_id != contract_id || !mined {
+        return None;
+    }
+    match entry.value {
+        Value::Amount(amount) => Some(amount),
+        Value::Token(_) | Value::None => None,
+    }
+}
+
+/// Balances of all seals holding `contract_id` tokens after the block at
+/// `height`. Incoming and outgoing amounts are summed separately, so the
+/// result does not depend on the order of ledger entries.
+fn seal_balances_at(
+    ledger: &Ledger,
+    contract_id: ContractId,
+    height: u32,
+) -> Result<BTreeMap<Outpoint, Amount256>, Error> {
+    let mut moved = BTreeMap::<Outpoint, (Amount256, Amount256)>::new();
+    for entry in ledger.entries() {
+        let Some(amount) = amount_at(entry, contract_id, height) else {
+            continue;
+        };
+        let (incoming, outgoing) = moved.entry(entry.account).or_default();
+        match entry.direction {
+            Direction::Incoming => *incoming = incoming.checked_add(amount)?,
+            Direction::Outgoing => *outgoing = outgoing.checked_add(amount)?,
+        }
+    }
+    let mut balances = BTreeMap::new();
+    for (seal, (incoming, outgoing)) in moved {
+        let balance = incoming.checked_sub(outgoing)?;
+        if balance != Amount256::ZERO {
+            balances.insert(seal, balance);
+        }
+    }
+    Ok(balances)
+}
+
+/// Balances of all accounts holding `contract_id` tokens after the block at
+/// `height`, computed from the indexed transfer history. `account_of` maps
+/// the seals holding tokens to the accounts owning them; seals it doesn't
+/// know are left out.
+pub fn balances_at(
+    ledger: &Ledger,
+    contract_id: ContractId,
+    height: u32,
+    account_of: impl Fn(Outpoint) -> Option<Account>,
+) -> Result<HashMap<Account, Amount256>, Error> {
+    let mut balances = HashMap::<Account, Amount256>::new();
+    for (seal, amount) in seal_balances_at(ledger, contract_id, height)? {
+        let Some(account) = account_of(seal) else {
+            continue;
+        };
+        let balance = balances.entry(account).or_insert(Amount256::ZERO);
+        *balance = balance.checked_add(amount)?;
+    }
+    Ok(balances)
+}
+
+pub fn balance_of_at(
+    ledger: &Ledger,
+    contract_id: ContractId,
+    account: Account,
+    height: u32,
+    account_of: impl Fn(Outpoint) -> Option<Account>,
+) -> Result<Amount256, Error> {
+    Ok(balances_at(ledger, contract_id, height, account_of)?
+        .get(&account)
+        .copied()
+        .unwrap_or(Amount256::ZERO))
+}
+
+pub fn total_supply_at(
+    ledger: &Ledger,
+    contract_id: ContractId,
+    height: u32,
+) -> Result<Amount256, Error> {
+    checked_sum(seal_balances_at(ledger, contract_id, height)?.into_values())
+}
+
+/// Balances frozen by a `Snapshot` the issuer committed to the contract in
+/// a transition mined at `committed_at`, `None` while it is tentative. The
+/// schema can't see witness heights, so a snapshot of a height above the
+/// committing block, which would let the issuer pick balances it hasn't
+/// seen yet, is rejected here with [`Error::InvalidProof`].
+pub fn balances_of_snapshot(
+    ledger: &Ledger,
+    contract_id: ContractId,
+    snapshot: Snapshot,
+    committed_at: Option<u32>,
+    account_of: impl Fn(Outpoint) -> Option<Account>,
+) -> Result<HashMap<Account, Amount256>, Error> {
+    if !committed_at.is_some_and(|height| snapshot.height <= height) {
+        return Err(Error::InvalidProof);
+    }
+    balances_at(ledger, contract_id, snapshot.height, account_of)
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::{Confined, SmallBlob};
+    use amplify::Wrapper;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{ScriptBuf, Sequence, Transaction, TxIn, TxOut};
+    use bp::{Chain, Txid};
+    use rgbstd::contract::{GenesisSeal, GraphSeal, Opout};
+    use rgbstd::schema::SchemaId;
+    use rgbstd::{
+        Assign, Assignments, Genesis, Input, Inputs, Operation, RevealedData, Transition,
+        TypedAssigns,
+    };
+
+    use super::*;
+    use crate::rgb_e20_schema::{nia_rgb_e20, OS_ASSETS};
+    use crate::rgb_resolver::FileResolver;
+
+    fn seal(n: u8) -> Outpoint { Outpoint::new([n; 32].into(), 0) }
+
+    fn amount(value: u64) -> RevealedData {
+        RevealedData::from(SmallBlob::try_from(Amount256::from(value).to_vec()).unwrap())
+    }
+
+    fn tx(n: u8) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: bitcoin::OutPoint::new(bitcoin::Txid::from_byte_array([n; 32]), 0),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: bitcoin::Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 1_000,
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    fn txid(n: u8) -> Txid { tx(n).txid().to_byte_array().into() }
+
+    fn genesis(schema: u8, allocations: &[(u8, u64)]) -> Genesis {
+        let assigns = allocations
+            .iter()
+            .map(|(n, value)| Assign::revealed(GenesisSeal::from(seal(*n)), amount(*value)))
+            .collect::<Vec<_>>();
+        Genesis {
+            ffv: zero!(),
+            schema_id: SchemaId::from([schema; 32]),
+            chain: Chain::Regtest,
+            metadata: none!(),
+            globals: none!(),
+            assignments: Assignments::from_inner(confined_bmap! {
+                OS_ASSETS => TypedAssigns::Structured(Confined::try_from(assigns).unwrap()),
+            }),
+            valencies: none!(),
+        }
+    }
+
+    /// Transfer of the `no`th genesis allocation to seal `to`.
+    fn transfer(genesis: &Genesis, no: u16, to: u8, value: u64) -> Transition {
+        let input = Input::with(Opout::new(genesis.id(), OS_ASSETS, no));
+        Transition {
+            ffv: zero!(),
+            contract_id: genesis.contract_id(),
+            transition_type: 20000,
+            metadata: none!(),
+            globals: none!(),
+            inputs: Inputs::from_inner(confined_bset! { input }),
+            assignments: Assignments::from_inner(confined_bmap! {
+                OS_ASSETS => TypedAssigns::Structured(confined_vec![
+                    Assign::revealed(GraphSeal::from(seal(to)), amount(value)),
+                ]),
+            }),
+            valencies: none!(),
+        }
+    }
+
+    /// Contract issuing 10 tokens to seal 1 and 5 to seal 2, which move to
+    /// seal 3 at height 100 and to seal 4 in a tentative transfer, next to
+    /// another contract issuing 7 tokens to seal 5.
+    fn ledger() -> (Ledger, ContractId, ContractId) {
+        let iimpl = nia_rgb_e20();
+        let other = genesis(0x5d, &[(5, 7)]);
+        let genesis = genesis(0x5c, &[(1, 10), (2, 5)]);
+        let mut resolver = FileResolver::default();
+        resolver.add(tx(1), Some(100), Some(1_700_000_000));
+        resolver.add(tx(2), None, None);
+        resolver.set_tip(101);
+
+        let mut ledger = Ledger::new();
+        let transitions = [
+            (transfer(&genesis, 0, 3, 10), txid(1)),
+            (transfer(&genesis, 1, 4, 5), txid(2)),
+        ];
+        ledger.index(&iimpl, &genesis, transitions, &resolver).unwrap();
+        ledger.index(&iimpl, &other, [], &resolver).unwrap();
+        (ledger, genesis.contract_id(), other.contract_id())
+    }
+
+    #[test]
+    fn seal_balances() {
+        let (ledger, contract_id, other) = ledger();
+        let balances = |height| seal_balances_at(&ledger, contract_id, height).unwrap();
+        let issued = bmap! {
+            seal(1) => Amount256::from(10u64),
+            seal(2) => Amount256::from(5u64),
+        };
+        assert_eq!(balances(0), issued);
+        assert_eq!(balances(99), issued);
+        let transferred = bmap! {
+            seal(2) => Amount256::from(5u64),
+            seal(3) => Amount256::from(10u64),
+        };
+        assert_eq!(balances(100), transferred);
+        // tentative transfers count at no height
+        assert_eq!(balances(u32::MAX), transferred);
+        assert_eq!(seal_balances_at(&ledger, other, 0).unwrap(), bmap! {
+            seal(5) => Amount256::from(7u64),
+        });
+    }
+
+    #[test]
+    fn total_supply() {
+        let (ledger, contract_id, other) = ledger();
+        for height in [0, 100, u32::MAX] {
+            assert_eq!(total_supply_at(&ledger, contract_id, height), Ok(Amount256::from(15u64)));
+            assert_eq!(total_supply_at(&ledger, other, height), Ok(Amount256::from(7u64)));
+        }
+        let unknown = ContractId::from([0xc0; 32]);
+        assert_eq!(total_supply_at(&ledger, unknown, 100), Ok(Amount256::ZERO));
+    }
+
+    #[test]
+    fn snapshot_height() {
+        let (ledger, contract_id, _) = ledger();
+        let snapshot = Snapshot { id: 1, height: 100 };
+        let balances = |committed_at| {
+            balances_of_snapshot(&ledger, contract_id, snapshot, committed_at, |_| None)
+        };
+        assert_eq!(balances(Some(100)), Ok(none!()));
+        assert_eq!(balances(Some(120)), Ok(none!()));
+        assert_eq!(balances(Some(99)), Err(Error::InvalidProof));
+        assert_eq!(balances(None), Err(Error::InvalidProof));
+    }
+}
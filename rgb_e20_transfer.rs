@@ -0,0 +1,177 @@
+//RGB-E20 batch transfers
+use rgbstd::contract::{ContractId, GraphSeal, Opout};
+use rgbstd::persistence::{Inventory, Stock};
+use rgbstd::Transition;
+
+use crate::rgb_alu::MAX_U256_STATES;
+use crate::rgb_e20_interface::{checked_sum, conserves, rgb_e20, Amount256, Error};
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum TransferError {
+    /// transfer does not pay anybody.
+    NoRecipients,
+    /// inputs exceed the payments, but no change seal is given.
+    NoChange,
+    /// transfer pays zero tokens to one of its recipients.
+    ZeroAmount,
+    /// transfer has {0} outputs including change, more than a transition can
+    /// assign.
+    TooManyOutputs(usize),
+    /// invalid transfer: {0:?}.
+    #[from]
+    Contract(Error),
+    /// stash error: {0}.
+    Stash(String),
+}
+
+/// Builds a single `BatchTransfer` transition paying many beneficiaries from
+/// a set of spent allocations, returning the rest as change.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct BatchTransfer {
+    contract_id: ContractId,
+    inputs: Vec<(Opout, Amount256)>,
+    payments: Vec<(GraphSeal, Amount256)>,
+    change: Option<GraphSeal>,
+}
+
+impl BatchTransfer {
+    pub fn with(
+        contract_id: ContractId,
+        inputs: impl IntoIterator<Item = (Opout, Amount256)>,
+    ) -> Self {
+        BatchTransfer {
+            contract_id,
+            inputs: inputs.into_iter().collect(),
+            payments: vec![],
+            change: None,
+        }
+    }
+
+    pub fn pay(mut self, seal: GraphSeal, amount: impl Into<Amount256>) -> Self {
+        self.payments.push((seal, amount.into()));
+        self
+    }
+
+    pub fn change(mut self, seal: GraphSeal) -> Self {
+        self.change = Some(seal);
+        self
+    }
+
+    /// Beneficiaries of the transition including change, checked against the
+    /// same conservation rule and state limit the schema enforces.
+    pub fn outputs(&self) -> Result<Vec<(GraphSeal, Amount256)>, TransferError> {
+        if self.payments.is_empty() {
+            return Err(TransferError::NoRecipients);
+        }
+        if self.payments.iter().any(|(_, amount)| *amount == Amount256::ZERO) {
+            return Err(TransferError::ZeroAmount);
+        }
+        let spent = checked_sum(self.inputs.iter().map(|(_, amount)| *amount))?;
+        let paid = checked_sum(self.payments.iter().map(|(_, amount)| *amount))?;
+        let rest = spent.checked_sub(paid)?;
+
+        let mut outputs = self.payments.clone();
+        if rest != Amount256::ZERO {
+            outputs.push((self.change.ok_or(TransferError::NoChange)?, rest));
+        }
+        if outputs.len() > MAX_U256_STATES as usize {
+            return Err(TransferError::TooManyOutputs(outputs.len()));
+        }
+        conserves(
+            self.inputs.iter().map(|(_, amount)| *amount),
+            outputs.iter().map(|(_, amount)| *amount),
+        )?;
+        Ok(outputs)
+    }
+
+    pub fn compose(&self, stock: &mut Stock) -> Result<Transition, TransferError> {
+        let outputs = self.outputs()?;
+        let mut builder = stock
+            .transition_builder(self.contract_id, rgb_e20().name, Some(tn!("BatchTransfer")))
+            .map_err(|e| TransferError::Stash(e.to_string()))?;
+        for (opout, _) in &self.inputs {
+            builder = builder.add_input(*opout).map_err(|e| TransferError::Stash(e.to_string()))?;
+        }
+        for (seal, amount) in outputs {
+            builder = builder
+                .add_data_state("beneficiary", seal, amount)
+                .map_err(|e| TransferError::Stash(e.to_string()))?;
+        }
+        builder
+            .complete_transition(self.contract_id)
+            .map_err(|e| TransferError::Stash(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rgbstd::contract::OpId;
+
+    use super::*;
+
+    fn contract_id() -> ContractId { ContractId::from([0x20; 32]) }
+
+    fn seal(n: u8) -> GraphSeal {
+        let outpoint: bp::Outpoint = format!("{}:0", format!("{n:02x}").repeat(32)).parse().unwrap();
+        GraphSeal::from(outpoint)
+    }
+
+    fn transfer(inputs: &[u64]) -> BatchTransfer {
+        BatchTransfer::with(
+            contract_id(),
+            inputs.iter().zip(1u8..).map(|(amount, n)| {
+                (Opout::new(OpId::from([n; 32]), 20000, 0), Amount256::from(*amount))
+            }),
+        )
+    }
+
+    fn amounts(outputs: &[(GraphSeal, Amount256)]) -> Vec<u64> {
+        outputs.iter().map(|(_, amount)| amount.value().low_u64()).collect()
+    }
+
+    #[test]
+    fn change() {
+        let batch = transfer(&[50, 30]).pay(seal(1), 20u64).pay(seal(2), 45u64);
+        assert_eq!(batch.outputs(), Err(TransferError::NoChange));
+        let change = seal(0xff);
+        let outputs = batch.change(change).outputs().unwrap();
+        assert_eq!(amounts(&outputs), vec![20, 45, 15]);
+        assert_eq!(outputs[2].0, change);
+
+        // exact payments need no change
+        let outputs = transfer(&[50, 30]).pay(seal(1), 80u64).outputs().unwrap();
+        assert_eq!(amounts(&outputs), vec![80]);
+        assert_eq!(
+            transfer(&[50]).pay(seal(1), 60u64).change(seal(0xff)).outputs(),
+            Err(TransferError::Contract(Error::InsufficientCoverage))
+        );
+    }
+
+    #[test]
+    fn no_recipients() {
+        assert_eq!(transfer(&[50]).change(seal(0xff)).outputs(), Err(TransferError::NoRecipients));
+        assert_eq!(
+            transfer(&[50]).pay(seal(1), 0u64).change(seal(0xff)).outputs(),
+            Err(TransferError::ZeroAmount)
+        );
+    }
+
+    #[test]
+    fn output_limit() {
+        let max = MAX_U256_STATES as u64;
+        let pay = |batch: BatchTransfer, count: u64| {
+            (1..=count).fold(batch, |batch, n| batch.pay(seal(n as u8), 1u64))
+        };
+        assert_eq!(pay(transfer(&[max]), max).outputs().map(|outputs| outputs.len()), Ok(16));
+        // the change counts towards the limit
+        assert_eq!(
+            pay(transfer(&[max + 1]), max).change(seal(0xff)).outputs(),
+            Err(TransferError::TooManyOutputs(17))
+        );
+        assert_eq!(
+            pay(transfer(&[max + 1]), max + 1).outputs(),
+            Err(TransferError::TooManyOutputs(17))
+        );
+    }
+}
@@ -0,0 +1,706 @@
+//RGB-E721 CLI
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use amplify::confinement::{SmallBlob, U16, U32};
+use amplify::hex::FromHex;
+use bp::Outpoint;
+use clap::{Parser, Subcommand};
+use rgbstd::contract::{ContractId, GenesisSeal, GraphSeal, Opout, TypedState};
+use rgbstd::interface::{rgb_e721, ContractBuilder, FilterIncludeAll, TransitionBuilder};
+use rgbstd::persistence::{Inventory, Stock};
+use rgbstd::resolvers::ResolveHeight;
+use rgbstd::stl::{
+    Allocation, Attachment, EmbeddedMedia, MediaType, OwnedFraction, TokenData, TokenIndex,
+};
+use serde::{Deserialize, Serialize};
+use strict_encoding::{FieldName, StrictDeserialize, StrictSerialize};
+
+use crate::{burn_supply, mint_supply, uda_rgb721, uda_schema, Approval, OwnerRight};
+
+const STOCK_FILE: &str = "stock.dat";
+const COLLECTIONS_DIR: &str = "collections";
+const MEDIA_DIR: &str = "media";
+
+#[derive(Parser, Clone, Debug)]
+#[command(name = "rgb-e721", about = "Mint, inspect and transfer RGB-E721 tokens")]
+pub struct Opts {
+    /// Directory holding the local stash.
+    #[arg(short, long, global = true, default_value = "~/.rgb-e721")]
+    pub data_dir: PathBuf,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Register a new collection in the local stash.
+    CreateCollection {
+        name: String,
+        symbol: String,
+        /// Hex address of the account owning the collection contract.
+        owner: String,
+        #[arg(long)]
+        base_uri: Option<String>,
+    },
+    /// Issue a token of a collection to a seal, optionally attaching a media file.
+    ///
+    /// The first token issues the collection contract; later tokens are
+    /// minted with a `Mint` transition spending the minter role held by
+    /// `--minter`, written to `--out`. The admin, minter and burner roles and
+    /// the owner right of a new contract go to `--admin`, or to the
+    /// beneficiary seal if not given.
+    Mint {
+        collection: String,
+        token_id: u32,
+        /// Beneficiary seal in `<txid>:<vout>` form.
+        seal: String,
+        #[arg(long)]
+        media: Option<PathBuf>,
+        #[arg(long)]
+        preview: Option<PathBuf>,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        details: Option<String>,
+        /// Seal receiving the admin roles and owner right, in `<txid>:<vout>` form.
+        #[arg(long)]
+        admin: Option<String>,
+        /// Outpoint holding the minter role.
+        #[arg(long)]
+        minter: Option<String>,
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Prepare a `Burn` transition destroying a token.
+    Burn {
+        contract_id: ContractId,
+        token_id: u32,
+        /// Outpoint holding the burner role.
+        burner: String,
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+    /// Print the outpoint owning a token.
+    OwnerOf { contract_id: ContractId, token_id: u32 },
+    /// Prepare a `TransferFrom` transition moving a token to a new seal.
+    Transfer {
+        contract_id: ContractId,
+        token_id: u32,
+        /// Beneficiary seal in `<txid>:<vout>` form.
+        to: String,
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+    /// Prepare a `SafeTransferFrom` transition moving a token to a new seal.
+    SafeTransfer {
+        contract_id: ContractId,
+        token_id: u32,
+        to: String,
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+    /// Prepare an `Approve` transition for a single token. The token stays
+    /// with its owner; the approved seal receives an `approval` for it.
+    Approve {
+        contract_id: ContractId,
+        token_id: u32,
+        approved: String,
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+    /// Prepare a `SetApprovalForAll` transition for an operator. All tokens
+    /// held by `owner` are spent and reassigned to it to authenticate.
+    SetApprovalForAll {
+        contract_id: ContractId,
+        /// Outpoint holding the owner's tokens.
+        owner: String,
+        operator: String,
+        #[arg(long)]
+        revoke: bool,
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+    /// Print the URI of a token.
+    TokenUri { contract_id: ContractId, token_id: u32 },
+    /// List known tokens and their `TokenData`.
+    List {
+        #[arg(long)]
+        contract_id: Option<ContractId>,
+    },
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Collection {
+    pub name: String,
+    pub symbol: String,
+    /// Hex address of the owner account.
+    pub owner: String,
+    pub base_uri: Option<String>,
+    pub contracts: Vec<ContractId>,
+}
+
+#[derive(Debug, Display, Error, From)]
+#[display(inner)]
+pub enum CliError {
+    #[from]
+    Io(std::io::Error),
+    #[from]
+    Json(serde_json::Error),
+    #[from]
+    Yaml(serde_yaml::Error),
+    #[display("collection {0} is not known to the stash")]
+    UnknownCollection(String),
+    #[display("collection {0} already exists")]
+    CollectionExists(String),
+    #[display("token {0} is not allocated in contract {1}")]
+    UnknownToken(u32, ContractId),
+    #[display("token {0} already exists in contract {1}, or is below its latest token id")]
+    TokenExists(u32, ContractId),
+    #[display("minting into an existing contract requires --out")]
+    NoOutput,
+    #[display("minting into an existing contract requires --minter")]
+    NoMinter,
+    #[display("no {0} right is held by {1}")]
+    MissingRole(&'static str, Outpoint),
+    #[display("invalid seal {0}")]
+    InvalidSeal(String),
+    #[display("invalid account address {0}")]
+    InvalidAccount(String),
+    #[display("invalid token name or details {0}")]
+    InvalidTokenData(String),
+    #[display("total supply of contract {0} would exceed the 64-bit range")]
+    SupplyOverflow(ContractId),
+    #[display("stash error: {0}")]
+    Stash(String),
+}
+
+struct DumbResolver;
+
+impl ResolveHeight for DumbResolver {
+    type Error = std::convert::Infallible;
+    fn resolve_height(&mut self, _txid: bp::Txid) -> Result<u32, Self::Error> { Ok(0) }
+}
+
+fn expand(path: &Path) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(&path.to_string_lossy()).to_string())
+}
+
+fn load_stock(data_dir: &Path) -> Result<Stock, CliError> {
+    let path = data_dir.join(STOCK_FILE);
+    if !path.exists() {
+        return Ok(Stock::default());
+    }
+    Stock::strict_deserialize_from_file::<U32>(path).map_err(|e| CliError::Stash(e.to_string()))
+}
+
+fn store_stock(data_dir: &Path, stock: &Stock) -> Result<(), CliError> {
+    stock
+        .strict_serialize_to_file::<U32>(data_dir.join(STOCK_FILE))
+        .map_err(|e| CliError::Stash(e.to_string()))
+}
+
+fn collection_path(data_dir: &Path, name: &str) -> PathBuf {
+    data_dir.join(COLLECTIONS_DIR).join(format!("{name}.json"))
+}
+
+fn load_collection(data_dir: &Path, name: &str) -> Result<Collection, CliError> {
+    let path = collection_path(data_dir, name);
+    if !path.exists() {
+        return Err(CliError::UnknownCollection(name.to_owned()));
+    }
+    Ok(serde_json::from_slice(&fs::read(path)?)?)
+}
+
+fn store_collection(data_dir: &Path, collection: &Collection) -> Result<(), CliError> {
+    let path = collection_path(data_dir, &collection.name);
+    fs::create_dir_all(path.parent().expect("collection dir"))?;
+    fs::write(path, serde_json::to_vec_pretty(collection)?)?;
+    Ok(())
+}
+
+fn parse_outpoint(s: &str) -> Result<Outpoint, CliError> {
+    s.parse().map_err(|_| CliError::InvalidSeal(s.to_owned()))
+}
+
+fn parse_account(s: &str) -> Result<Account, CliError> {
+    Vec::<u8>::from_hex(s.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| <[u8; 20]>::try_from(bytes).ok())
+        .map(Account::from)
+        .ok_or_else(|| CliError::InvalidAccount(s.to_owned()))
+}
+
+fn attach(data_dir: &Path, path: &Path) -> Result<Attachment, CliError> {
+    let data = fs::read(path)?;
+    let ty = MediaType::with(
+        mime_guess::from_path(path).first_or_octet_stream().essence_str(),
+    );
+    let attachment = Attachment {
+        ty,
+        digest: *blake3::hash(&data).as_bytes(),
+    };
+    let dir = data_dir.join(MEDIA_DIR);
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(attachment.digest.to_hex()), data)?;
+    Ok(attachment)
+}
+
+fn preview(path: &Path) -> Result<EmbeddedMedia, CliError> {
+    let data = fs::read(path)?;
+    Ok(EmbeddedMedia {
+        ty: MediaType::with(mime_guess::from_path(path).first_or_octet_stream().essence_str()),
+        data: SmallBlob::try_from(data).map_err(|e| CliError::Stash(e.to_string()))?,
+    })
+}
+
+fn token_allocation(
+    stock: &Stock,
+    contract_id: ContractId,
+    token_id: u32,
+) -> Result<(Outpoint, Allocation), CliError> {
+    let contract = stock
+        .contract_iface(contract_id, rgb_e721().iface_id())
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    contract
+        .data("beneficiary", &FilterIncludeAll)
+        .map_err(|e| CliError::Stash(e.to_string()))?
+        .into_iter()
+        .find(|alloc| alloc.value.token_index() == TokenIndex::from(token_id))
+        .map(|alloc| (alloc.owner, alloc.value))
+        .ok_or(CliError::UnknownToken(token_id, contract_id))
+}
+
+/// Spendable opouts of the given token allocations held by `owner`.
+fn token_inputs(
+    stock: &Stock,
+    contract_id: ContractId,
+    owner: Outpoint,
+    allocations: &[Allocation],
+) -> Result<Vec<(Opout, Allocation)>, CliError> {
+    let state = stock
+        .state_for_outpoints(contract_id, [owner])
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    let mut inputs = vec![];
+    for allocation in allocations {
+        let encoded = allocation
+            .to_strict_serialized::<U16>()
+            .map_err(|e| CliError::Stash(e.to_string()))?;
+        let opout = state
+            .iter()
+            .find(|(_, state)| {
+                matches!(state, TypedState::Structured(data) if data.as_ref() == encoded.as_slice())
+            })
+            .map(|(opout, _)| *opout)
+            .ok_or(CliError::UnknownToken(allocation.token_index().into(), contract_id))?;
+        inputs.push((opout, *allocation));
+    }
+    Ok(inputs)
+}
+
+/// Spends the `role` right held by `holder` and assigns it back to the same
+/// outpoint, authorizing the transition without giving the role up.
+fn add_role(
+    stock: &Stock,
+    contract_id: ContractId,
+    builder: TransitionBuilder,
+    role: &'static str,
+    holder: Outpoint,
+) -> Result<TransitionBuilder, CliError> {
+    let ty = builder
+        .assignments_type(&FieldName::from(role))
+        .ok_or(CliError::MissingRole(role, holder))?;
+    let opout = stock
+        .state_for_outpoints(contract_id, [holder])
+        .map_err(|e| CliError::Stash(e.to_string()))?
+        .into_keys()
+        .find(|opout| opout.ty == ty)
+        .ok_or(CliError::MissingRole(role, holder))?;
+    builder
+        .add_input(opout)
+        .and_then(|b| b.add_rights(role, GraphSeal::from(holder)))
+        .map_err(|e| CliError::Stash(e.to_string()))
+}
+
+/// Opout, owner and allocation of a single token.
+fn token_input(
+    stock: &Stock,
+    contract_id: ContractId,
+    token_id: u32,
+) -> Result<(Opout, Outpoint, Allocation), CliError> {
+    let (owner, allocation) = token_allocation(stock, contract_id, token_id)?;
+    let (opout, _) = token_inputs(stock, contract_id, owner, &[allocation])?.remove(0);
+    Ok((opout, owner, allocation))
+}
+
+fn token_data(stock: &Stock, contract_id: ContractId) -> Result<Vec<TokenData>, CliError> {
+    let contract = stock
+        .contract_iface(contract_id, rgb_e721().iface_id())
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    Ok(contract
+        .global("tokens")
+        .map_err(|e| CliError::Stash(e.to_string()))?
+        .iter()
+        .map(TokenData::from_strict_val_unchecked)
+        .collect())
+}
+
+fn total_supply(stock: &Stock, contract_id: ContractId) -> Result<u64, CliError> {
+    let contract = stock
+        .contract_iface(contract_id, rgb_e721().iface_id())
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    Ok(contract
+        .global("total_supply")
+        .map_err(|e| CliError::Stash(e.to_string()))?
+        .last()
+        .map(u64::from_strict_val_unchecked)
+        .unwrap_or_default())
+}
+
+/// `total_supply` is kept as a 64-bit amount, so larger supplies can't be
+/// committed.
+fn supply_u64(supply: U256, contract_id: ContractId) -> Result<u64, CliError> {
+    if supply > U256::from(u64::MAX) {
+        return Err(CliError::SupplyOverflow(contract_id));
+    }
+    Ok(supply.low_u64())
+}
+
+fn burned_ids(stock: &Stock, contract_id: ContractId) -> Result<Vec<U256>, CliError> {
+    let contract = stock
+        .contract_iface(contract_id, rgb_e721().iface_id())
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    Ok(contract
+        .global("burned")
+        .map_err(|e| CliError::Stash(e.to_string()))?
+        .iter()
+        .map(|id| U256::from(u64::from_strict_val_unchecked(id)))
+        .collect())
+}
+
+fn prepare_burn(
+    stock: &mut Stock,
+    contract_id: ContractId,
+    token_id: u32,
+    burner: &str,
+    out: &Path,
+) -> Result<(), CliError> {
+    let (opout, _, _) = token_input(stock, contract_id, token_id)?;
+    let burner = parse_outpoint(burner)?;
+    let supply = burn_supply(U256::from(total_supply(stock, contract_id)?), &[U256::from(token_id)])
+        .map_err(|_| CliError::UnknownToken(token_id, contract_id))?;
+    let supply = supply_u64(supply, contract_id)?;
+
+    // only the burned token is spent, other tokens at its outpoint stay put
+    let builder = stock
+        .transition_builder(contract_id, rgb_e721().name, Some(tn!("Burn")))
+        .and_then(|b| b.add_input(opout))
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    let transition = add_role(stock, contract_id, builder, "burner_role", burner)?
+        .add_global_state("burned", token_id as u64)
+        .and_then(|b| b.add_global_state("total_supply", supply))
+        .and_then(|b| b.complete_transition(contract_id))
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    transition
+        .strict_serialize_to_file::<U32>(out)
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    eprintln!("Burn transition for token {token_id} written to {}", out.display());
+    Ok(())
+}
+
+fn prepare_mint(
+    stock: &mut Stock,
+    contract_id: ContractId,
+    data: TokenData,
+    seal: &str,
+    minter: &str,
+    out: &Path,
+) -> Result<(), CliError> {
+    let token_id = u32::from(data.index);
+    let existing = token_data(stock, contract_id)?
+        .into_iter()
+        .map(|data| U256::from(u32::from(data.index)));
+    let contract = stock
+        .contract_iface(contract_id, rgb_e721().iface_id())
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    let supply = total_supply(stock, contract_id)?;
+    let burned = burned_ids(stock, contract_id)?;
+    let supply = mint_supply(existing, burned, &[U256::from(token_id)], U256::from(supply))
+        .map_err(|_| CliError::TokenExists(token_id, contract_id))?;
+    let supply = supply_u64(supply, contract_id)?;
+    let minter = parse_outpoint(minter)?;
+
+    // tokens of a locked collection can only be minted as locked
+    let locked = !contract
+        .global("locked")
+        .map_err(|e| CliError::Stash(e.to_string()))?
+        .is_empty();
+    let beneficiary = if locked { "locked_beneficiary" } else { "beneficiary" };
+
+    let seal = GraphSeal::from(parse_outpoint(seal)?);
+    let allocation = Allocation::with(data.index, OwnedFraction::from(1));
+    let builder = stock
+        .transition_builder(contract_id, rgb_e721().name, Some(tn!("Mint")))
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    let transition = add_role(stock, contract_id, builder, "minter_role", minter)?
+        .add_global_state("tokens", data)
+        .and_then(|b| b.add_global_state("total_supply", supply))
+        .and_then(|b| b.add_data_state(beneficiary, seal, allocation))
+        .and_then(|b| b.complete_transition(contract_id))
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    transition
+        .strict_serialize_to_file::<U32>(out)
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    eprintln!("Mint transition for token {token_id} written to {}", out.display());
+    Ok(())
+}
+
+fn prepare_transition(
+    stock: &mut Stock,
+    contract_id: ContractId,
+    op: &'static str,
+    token_id: u32,
+    to: &str,
+    out: &Path,
+) -> Result<(), CliError> {
+    let (opout, _, allocation) = token_input(stock, contract_id, token_id)?;
+    let seal = GraphSeal::from(parse_outpoint(to)?);
+    let transition = stock
+        .transition_builder(contract_id, rgb_e721().name, Some(tn!(op)))
+        .and_then(|builder| builder.add_input(opout))
+        .and_then(|builder| builder.add_data_state("beneficiary", seal, allocation))
+        .and_then(|builder| builder.complete_transition(contract_id))
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    transition
+        .strict_serialize_to_file::<U32>(out)
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    eprintln!("{op} transition for token {token_id} written to {}", out.display());
+    Ok(())
+}
+
+fn prepare_approval(
+    stock: &mut Stock,
+    contract_id: ContractId,
+    token_id: u32,
+    approved: &str,
+    out: &Path,
+) -> Result<(), CliError> {
+    let (opout, owner, allocation) = token_input(stock, contract_id, token_id)?;
+    let approval = Approval {
+        token_id: U256::from(token_id),
+    };
+    let approved = GraphSeal::from(parse_outpoint(approved)?);
+    let transition = stock
+        .transition_builder(contract_id, rgb_e721().name, Some(tn!("Approve")))
+        .and_then(|b| b.add_input(opout))
+        .and_then(|b| b.add_data_state("beneficiary", GraphSeal::from(owner), allocation))
+        .and_then(|b| b.add_data_state("approval", approved, approval))
+        .and_then(|b| b.complete_transition(contract_id))
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    transition
+        .strict_serialize_to_file::<U32>(out)
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    eprintln!("Approve transition for token {token_id} written to {}", out.display());
+    Ok(())
+}
+
+fn prepare_approval_for_all(
+    stock: &mut Stock,
+    contract_id: ContractId,
+    owner: &str,
+    operator: &str,
+    approved: bool,
+    out: &Path,
+) -> Result<(), CliError> {
+    let owner = parse_outpoint(owner)?;
+    let contract = stock
+        .contract_iface(contract_id, rgb_e721().iface_id())
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    let allocations = contract
+        .data("beneficiary", &FilterIncludeAll)
+        .map_err(|e| CliError::Stash(e.to_string()))?
+        .into_iter()
+        .filter(|alloc| alloc.owner == owner)
+        .map(|alloc| alloc.value)
+        .collect::<Vec<_>>();
+    if allocations.is_empty() {
+        return Err(CliError::Stash(format!("no tokens of {contract_id} are held by {owner}")));
+    }
+    let inputs = token_inputs(stock, contract_id, owner, &allocations)?;
+
+    let mut builder = stock
+        .transition_builder(contract_id, rgb_e721().name, Some(tn!("SetApprovalForAll")))
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    // the script requires spent tokens to come back in the same order
+    for (opout, _) in &inputs {
+        builder = builder.add_input(*opout).map_err(|e| CliError::Stash(e.to_string()))?;
+    }
+    for (_, allocation) in inputs {
+        builder = builder
+            .add_data_state("beneficiary", GraphSeal::from(owner), allocation)
+            .map_err(|e| CliError::Stash(e.to_string()))?;
+    }
+    builder = builder
+        .add_global_state("approved", approved)
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    if approved {
+        let seal = GraphSeal::from(parse_outpoint(operator)?);
+        builder = builder.add_rights("operator", seal).map_err(|e| CliError::Stash(e.to_string()))?;
+    }
+    let transition =
+        builder.complete_transition(contract_id).map_err(|e| CliError::Stash(e.to_string()))?;
+    transition
+        .strict_serialize_to_file::<U32>(out)
+        .map_err(|e| CliError::Stash(e.to_string()))?;
+    eprintln!("SetApprovalForAll transition written to {}", out.display());
+    Ok(())
+}
+
+pub fn exec(opts: Opts) -> Result<(), CliError> {
+    let data_dir = expand(&opts.data_dir);
+    fs::create_dir_all(&data_dir)?;
+    let mut stock = load_stock(&data_dir)?;
+
+    match opts.command {
+        Command::CreateCollection { name, symbol, owner, base_uri } => {
+            if collection_path(&data_dir, &name).exists() {
+                return Err(CliError::CollectionExists(name));
+            }
+            parse_account(&owner)?;
+            store_collection(&data_dir, &Collection {
+                name,
+                symbol,
+                owner,
+                base_uri,
+                contracts: vec![],
+            })?;
+        }
+        Command::Mint {
+            collection,
+            token_id,
+            seal,
+            media,
+            preview: prev,
+            name,
+            details,
+            admin,
+            minter,
+            out,
+        } => {
+            let mut coll = load_collection(&data_dir, &collection)?;
+            let index = TokenIndex::from(token_id);
+            let data = TokenData {
+                index,
+                ticker: None,
+                name: name
+                    .map(|n| n.parse().map_err(|_| CliError::InvalidTokenData(n)))
+                    .transpose()?,
+                details: details
+                    .map(|d| d.parse().map_err(|_| CliError::InvalidTokenData(d)))
+                    .transpose()?,
+                preview: prev.as_deref().map(preview).transpose()?,
+                media: media.as_deref().map(|p| attach(&data_dir, p)).transpose()?,
+                attachments: none!(),
+                reserves: None,
+            };
+            if let Some(contract_id) = coll.contracts.first().copied() {
+                let out = out.ok_or(CliError::NoOutput)?;
+                let minter = minter.ok_or(CliError::NoMinter)?;
+                prepare_mint(&mut stock, contract_id, data, &seal, &minter, &out)?;
+                return store_stock(&data_dir, &stock);
+            }
+            let owner = OwnerRight {
+                owner: parse_account(&coll.owner)?,
+            };
+            let admin = parse_outpoint(admin.as_deref().unwrap_or(&seal))?;
+            let seal = GenesisSeal::from(parse_outpoint(&seal)?);
+            let contract = ContractBuilder::with(rgb_e721(), uda_schema(), uda_rgb721())
+                .and_then(|b| b.add_global_state("name", coll.name.as_str()))
+                .and_then(|b| b.add_global_state("symbol", coll.symbol.as_str()))
+                .and_then(|b| b.add_global_state("total_supply", 1u64))
+                .and_then(|b| b.add_global_state("tokens", data))
+                .and_then(|b| b.add_global_state("paused", false))
+                .and_then(|b| {
+                    b.add_data_state("beneficiary", seal, Allocation::with(index, OwnedFraction::from(1)))
+                })
+                .and_then(|b| b.add_rights("admin_role", GenesisSeal::from(admin)))
+                .and_then(|b| b.add_rights("minter_role", GenesisSeal::from(admin)))
+                .and_then(|b| b.add_rights("burner_role", GenesisSeal::from(admin)))
+                .and_then(|b| b.add_data_state("owner", GenesisSeal::from(admin), owner))
+                .and_then(|b| b.issue_contract())
+                .map_err(|e| CliError::Stash(e.to_string()))?;
+            let contract_id = contract.contract_id();
+            stock
+                .import_contract(contract, &mut DumbResolver)
+                .map_err(|e| CliError::Stash(e.to_string()))?;
+            coll.contracts.push(contract_id);
+            store_collection(&data_dir, &coll)?;
+            println!("{contract_id}");
+        }
+        Command::Burn { contract_id, token_id, burner, out } => {
+            prepare_burn(&mut stock, contract_id, token_id, &burner, &out)?;
+        }
+        Command::OwnerOf { contract_id, token_id } => {
+            let (owner, _) = token_allocation(&stock, contract_id, token_id)?;
+            println!("{owner}");
+        }
+        Command::Transfer { contract_id, token_id, to, out } => {
+            prepare_transition(&mut stock, contract_id, "TransferFrom", token_id, &to, &out)?;
+        }
+        Command::SafeTransfer { contract_id, token_id, to, out } => {
+            prepare_transition(&mut stock, contract_id, "SafeTransferFrom", token_id, &to, &out)?;
+        }
+        Command::Approve { contract_id, token_id, approved, out } => {
+            prepare_approval(&mut stock, contract_id, token_id, &approved, &out)?;
+        }
+        Command::SetApprovalForAll { contract_id, owner, operator, revoke, out } => {
+            prepare_approval_for_all(&mut stock, contract_id, &owner, &operator, !revoke, &out)?;
+        }
+        Command::TokenUri { contract_id, token_id } => {
+            let data = token_data(&stock, contract_id)?
+                .into_iter()
+                .find(|data| data.index == TokenIndex::from(token_id))
+                .ok_or(CliError::UnknownToken(token_id, contract_id))?;
+            let base_uri = fs::read_dir(data_dir.join(COLLECTIONS_DIR))?
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| fs::read(entry.path()).ok())
+                .filter_map(|bytes| serde_json::from_slice::<Collection>(&bytes).ok())
+                .find(|coll| coll.contracts.contains(&contract_id))
+                .and_then(|coll| coll.base_uri);
+            match (base_uri, data.media) {
+                (Some(base), _) => println!("{}/{token_id}", base.trim_end_matches('/')),
+                (None, Some(media)) => println!("attachment:{}", media.digest.to_hex()),
+                (None, None) => println!("-"),
+            }
+        }
+        Command::List { contract_id } => {
+            let ids = match contract_id {
+                Some(id) => vec![id],
+                None => stock
+                    .contract_ids_by_iface(&rgb_e721().name)
+                    .map_err(|e| CliError::Stash(e.to_string()))?
+                    .into_iter()
+                    .collect(),
+            };
+            for id in ids {
+                for data in token_data(&stock, id)? {
+                    let owner = token_allocation(&stock, id, data.index.into())
+                        .map(|(owner, _)| owner.to_string())
+                        .unwrap_or_else(|_| s!("-"));
+                    println!("{id}\t{}\t{owner}", data.index);
+                    println!("{}", serde_yaml::to_string(&data)?);
+                }
+            }
+        }
+    }
+
+    store_stock(&data_dir, &stock)
+}
+
+fn main() {
+    if let Err(err) = exec(Opts::parse()) {
+        eprintln!("rgb-e721: {err}");
+        std::process::exit(1);
+    }
+}
@@ -1,15 +1,19 @@
 //RGB-E721
 use amplify::confinement::LargeVec;
 use bp::bc::stl::bitcoin_stl;
+use strict_types::stl::std_stl;
 use strict_types::{CompileError, LibBuilder, TypeLib};
 
 use super::{
     AssignIface, GenesisIface, GlobalIface, Iface, OwnedIface, Req, TransitionIface, VerNo,
 };
 use crate::interface::contract::OutpointFilter;
+use crate::interface::rgb_ownable_stl;
 use crate::interface::{ArgSpec, ContractIface, FungibleAllocation};
-use crate::stl::{Amount, ContractData, DivisibleAssetSpec, StandardTypes};
-use std::collections::HashMap;
+use crate::stl::{
+    rgb_contract_stl, Amount, ContractData, DivisibleAssetSpec, StandardTypes, TokenData,
+};
+use std::collections::{BTreeSet, HashMap};
 
 pub const LIB_NAME_RGB_E721: &str = "RGB-E721";
 
@@ -18,6 +22,14 @@ const TOKEN_NOT_EXIST: u8 = 2;
 const INVALID_PROOF: u8 = 3;
 const INSUFFICIENT_RESERVES: u8 = 4;
 const ISSUE_EXCEEDS_ALLOWANCE: u8 = 5;
+const INVALID_ROYALTY: u8 = 6;
+const TOKEN_LOCKED: u8 = 7;
+const PAUSED: u8 = 8;
+const UNAUTHORIZED: u8 = 9;
+const TOKEN_EXISTS: u8 = 10;
+const AMOUNT_OVERFLOW: u8 = 11;
+
+pub const ROYALTY_BPS_DENOMINATOR: u16 = 10000;
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
@@ -30,6 +42,159 @@ pub enum Error {
     InvalidProof = INVALID_PROOF,
     InsufficientReserves = INSUFFICIENT_RESERVES,
     IssueExceedsAllowance = ISSUE_EXCEEDS_ALLOWANCE,
+    InvalidRoyalty = INVALID_ROYALTY,
+    TokenLocked = TOKEN_LOCKED,
+    Paused = PAUSED,
+    Unauthorized = UNAUTHORIZED,
+    TokenExists = TOKEN_EXISTS,
+    AmountOverflow = AMOUNT_OVERFLOW,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E721)]
+pub struct Royalty {
+    // leads the value so the schema script reads it at a fixed offset
+    pub bps: u16,
+    pub receiver: Account,
+}
+
+impl Royalty {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.bps > ROYALTY_BPS_DENOMINATOR {
+            return Err(Error::InvalidRoyalty);
+        }
+        Ok(())
+    }
+
+    pub fn amount(&self, sale_price: U256) -> Result<U256, Error> {
+        self.validate()?;
+        sale_price
+            .checked_mul(U256::from(self.bps))
+            .map(|value| value / U256::from(ROYALTY_BPS_DENOMINATOR))
+            .ok_or(Error::AmountOverflow)
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E721)]
+pub struct TokenRoyalty {
+    pub token_id: U256,
+    pub royalty: Royalty,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E721)]
+pub struct UserRight {
+    pub token_id: U256,
+    pub user: Account,
+    pub expires: i64,
+    /// Timestamp of the `SetUser` transition assigning the right.
+    pub since: i64,
+}
+
+/// ERC-721 single-token approval, assigned to the approved account's seal.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_E721)]
+pub struct Approval {
+    pub token_id: U256,
+}
+
+impl UserRight {
+    pub fn is_active(&self, now: i64) -> bool { self.expires > now }
+}
+
+/// ERC-4907 `userOf`: the user right set most recently for the token, or
+/// `None` if it has expired at `now`. ERC-4907 clears the user when the
+/// token changes hands, so rights set before `transferred`, the time of the
+/// token's latest transfer to a new owner, were granted by a previous owner
+/// and are ignored, as are rights set after `now`. Rights are ordered by the
+/// time they were set, then by expiry, so the result does not depend on the
+/// order in which the contract state lists them.
+pub fn active_user<'a>(
+    rights: impl IntoIterator<Item = &'a UserRight>,
+    token_id: U256,
+    transferred: Option<i64>,
+    now: i64,
+) -> Option<&'a UserRight> {
+    rights
+        .into_iter()
+        .filter(|right| right.token_id == token_id && right.since <= now)
+        .filter(|right| transferred.map_or(true, |time| right.since >= time))
+        .max_by_key(|right| (right.since, right.expires))
+        .filter(|right| right.is_active(now))
+}
+
+/// Resolves ERC-2981 `royaltyInfo` from the collection default and the
+/// per-token overrides kept in global state.
+pub fn royalty_info(
+    default: Royalty,
+    overrides: &[TokenRoyalty],
+    token_id: U256,
+    sale_price: U256,
+) -> Result<(Account, U256), Error> {
+    let royalty = overrides
+        .iter()
+        .rev()
+        .find(|item| item.token_id == token_id)
+        .map(|item| item.royalty)
+        .unwrap_or(default);
+    Ok((royalty.receiver, royalty.amount(sale_price)?))
+}
+
+/// Checks that a mint introduces token ids in increasing order, above every
+/// existing id, as the schema requires, and returns the resulting
+/// `total_supply`. Burned ids count as existing, so they can never be
+/// re-minted.
+pub fn mint_supply(
+    existing: impl IntoIterator<Item = U256>,
+    burned: impl IntoIterator<Item = U256>,
+    minted: &[U256],
+    total_supply: U256,
+) -> Result<U256, Error> {
+    let mut last = existing.into_iter().chain(burned).max();
+    for token_id in minted {
+        if last.is_some_and(|last| *token_id <= last) {
+            return Err(Error::TokenExists);
+        }
+        last = Some(*token_id);
+    }
+    total_supply
+        .checked_add(U256::from(minted.len() as u64))
+        .ok_or(Error::SupplyMismatch)
+}
+
+/// Returns the `total_supply` left after burning `burned` tokens.
+pub fn burn_supply(total_supply: U256, burned: &[U256]) -> Result<U256, Error> {
+    total_supply
+        .checked_sub(U256::from(burned.len() as u64))
+        .ok_or(Error::SupplyMismatch)
+}
+
+/// ERC-721 enumeration: all minted token ids in mint order, without the
+/// burned ones.
+pub fn enumerate_tokens(
+    tokens: impl IntoIterator<Item = U256>,
+    burned: impl IntoIterator<Item = U256>,
+) -> Vec<U256> {
+    let burned = burned.into_iter().collect::<BTreeSet<_>>();
+    tokens.into_iter().filter(|id| !burned.contains(id)).collect()
+}
+
+/// ERC-721 `tokenByIndex` over the enumeration returned by
+/// [`enumerate_tokens`].
+pub fn token_by_index(
+    tokens: impl IntoIterator<Item = U256>,
+    burned: impl IntoIterator<Item = U256>,
+    index: usize,
+) -> Result<U256, Error> {
+    enumerate_tokens(tokens, burned)
+        .get(index)
+        .copied()
+        .ok_or(Error::NonEqualAmounts)
 }
 
 type Balances = HashMap<Account, u64>;
@@ -48,7 +213,26 @@ pub struct RgbE721 {
     base_uri: Option<String>
 }
 
+fn _rgb_e721_stl() -> Result<TypeLib, CompileError> {
+    LibBuilder::new(libname!(LIB_NAME_RGB_E721), tiny_bset! {
+        std_stl().to_dependency(),
+        bitcoin_stl().to_dependency(),
+        rgb_contract_stl().to_dependency(),
+        rgb_ownable_stl().to_dependency()
+    })
+    .transpile::<Royalty>()
+    .transpile::<TokenRoyalty>()
+    .transpile::<UserRight>()
+    .transpile::<Approval>()
+    .transpile::<Error>()
+    .compile()
+}
+
+pub fn rgb_e721_stl() -> TypeLib { _rgb_e721_stl().expect("invalid strict type RGB-E721 library") }
+
 pub fn rgb_e721() -> Iface {
+    let types = StandardTypes::with(rgb_e721_stl());
+
     Iface {
         version: VerNo::V1,
         name: tn!("RGB-E721"),
@@ -58,6 +242,23 @@ pub fn rgb_e721() -> Iface {
             fname!("total_supply") => GlobalIface::required(types.get("RGBContract.Amount")),
             fname!("token_by_index") => GlobalIface::one_or_many(types.get("RGBContract.Amount")),
             fname!("token_of_owner_by_index") => GlobalIface::one_or_many(types.get("RGBContract.Amount")),
+            fname!("tokens") => GlobalIface::none_or_many(types.get("RGBContract.TokenData")),
+            fname!("locked") => GlobalIface::optional(types.get("Std.Bool")),
+            fname!("paused") => GlobalIface::required(types.get("Std.Bool")),
+            fname!("base_uri") => GlobalIface::optional(types.get("RGBContract.Details")),
+            fname!("burned") => GlobalIface::none_or_many(types.get("RGBContract.Amount")),
+            fname!("approved") => GlobalIface::none_or_many(types.get("Std.Bool")),
+        },
+        assignments: tiny_bmap! {
+            fname!("beneficiary") => AssignIface::public(OwnedIface::Data(types.get("RGBContract.Allocation")), Req::NoneOrMore),
+            fname!("locked_beneficiary") => AssignIface::public(OwnedIface::Data(types.get("RGBContract.Allocation")), Req::NoneOrMore),
+            fname!("admin_role") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
+            fname!("minter_role") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
+            fname!("burner_role") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
+            fname!("pauser_role") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
+            fname!("owner") => AssignIface::public(OwnedIface::Data(types.get("RGB-Ownable.OwnerRight")), Req::Optional),
+            fname!("approval") => AssignIface::public(OwnedIface::Data(types.get("RGB-E721.Approval")), Req::NoneOrMore),
+            fname!("operator") => AssignIface::private(OwnedIface::Rights, Req::NoneOrMore),
         },
         genesis: GenesisIface {
             metadata: Some(types.get("RGBContract.Meta")),
@@ -67,7 +268,8 @@ pub fn rgb_e721() -> Iface {
             assignments: none!(),
             valencies: none!(),
             errors: tiny_bset! {
-                INSUFFICIENT_RESERVES
+                INSUFFICIENT_RESERVES,
+                TOKEN_LOCKED
             },
         },
         transitions: tiny_bmap! {
@@ -95,7 +297,9 @@ pub fn rgb_e721() -> Iface {
                 },
                 valencies: none!(),
                 errors: tiny_bset! {
-                    NON_EQUAL_AMOUNTS
+                    NON_EQUAL_AMOUNTS,
+                    TOKEN_LOCKED,
+                    PAUSED
                 },
                 default_assignment: Some(fname!("safe_transfer")),
             },
@@ -114,7 +318,9 @@ pub fn rgb_e721() -> Iface {
                     SUPPLY_MISMATCH,
                     INVALID_PROOF,
                     ISSUE_EXCEEDS_ALLOWANCE,
-                    INSUFFICIENT_RESERVES
+                    INSUFFICIENT_RESERVES,
+                    TOKEN_LOCKED,
+                    PAUSED
                 },
                 default_assignment: Some(fname!("transfer_from")),
             },
@@ -123,26 +329,28 @@ pub fn rgb_e721() -> Iface {
                 metadata: None,
                 globals: none!(),
                 inputs: tiny_bmap! {
-                    fname!("approved") => ArgSpec::from_required("account"),
-                    fname!("token_id") => ArgSpec::from_required("Index"),
+                    fname!("used") => ArgSpec::from_required("beneficiary"),
                 },
-                errors: none!(),
-                default_assignment: Some(fname!("approve")),
+                errors: tiny_bset! {
+                    UNAUTHORIZED,
+                    PAUSED
+                },
+                default_assignment: Some(fname!("approval")),
             },
             tn!("SetApprovalForAll") => TransitionIface {
                 optional: true,
                 metadata: None,,
-                globals: none!(),
+                globals: tiny_bmap! {
+                    fname!("approved") => ArgSpec::required(),
+                },
                 inputs: tiny_bmap! {
-                    fname!("operator") => ArgSpec::from_required("Account"),
-                    fname!("approved") => ArgSpec::from_required("Bool"),
+                    fname!("used") => ArgSpec::from_non_empty("beneficiary"),
                 },
                 errors: tiny_bset! {
-                    SUPPLY_MISMATCH,
-                    INVALID_PROOF,
-                    INSUFFICIENT_COVERAGE
+                    UNAUTHORIZED,
+                    PAUSED
                 },
-                default_assignment: Some(fname!("set_approval_for_all")),
+                default_assignment: Some(fname!("operator")),
             },
             tn!("GetApproved") => TransitionIface {
                 optional: true,
@@ -169,6 +377,103 @@ pub fn rgb_e721() -> Iface {
                     INSUFFICIENT_COVERAGE
                 },
                 default_assignment: Some(fname!("is_approved_for_all")),
+            },
+            tn!("Locked") => TransitionIface {
+                optional: true,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("token_id") => ArgSpec::from_required("Index"),
+                },
+                valencies: none!(),
+                errors: none!(),
+                default_assignment: None,
+            },
+            tn!("Mint") => TransitionIface {
+                optional: true,
+                metadata: None,
+                globals: tiny_bmap! {
+                    fname!("tokens") => ArgSpec::non_empty(),
+                    fname!("total_supply") => ArgSpec::required(),
+                },
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("minter_role"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    SUPPLY_MISMATCH,
+                    TOKEN_EXISTS,
+                    UNAUTHORIZED,
+                    PAUSED
+                },
+                default_assignment: Some(fname!("beneficiary")),
+            },
+            tn!("Burn") => TransitionIface {
+                optional: true,
+                metadata: None,
+                globals: tiny_bmap! {
+                    fname!("burned") => ArgSpec::non_empty(),
+                    fname!("total_supply") => ArgSpec::required(),
+                },
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_non_empty("beneficiary"),
+                    fname!("burner") => ArgSpec::from_required("burner_role"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    SUPPLY_MISMATCH,
+                    TOKEN_NOT_EXIST,
+                    UNAUTHORIZED,
+                    PAUSED
+                },
+                default_assignment: Some(fname!("burner_role")),
+            },
+            tn!("Pause") => TransitionIface {
+                optional: true,
+                metadata: None,
+                globals: tiny_bmap! {
+                    fname!("paused") => ArgSpec::required(),
+                },
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("pauser_role"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    INVALID_PROOF,
+                    UNAUTHORIZED
+                },
+                default_assignment: Some(fname!("pauser_role")),
+            },
+            tn!("Unpause") => TransitionIface {
+                optional: true,
+                metadata: None,
+                globals: tiny_bmap! {
+                    fname!("paused") => ArgSpec::required(),
+                },
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("pauser_role"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    INVALID_PROOF,
+                    UNAUTHORIZED
+                },
+                default_assignment: Some(fname!("pauser_role")),
+            },
+            tn!("SetBaseUri") => TransitionIface {
+                optional: true,
+                metadata: None,
+                globals: tiny_bmap! {
+                    fname!("base_uri") => ArgSpec::required(),
+                },
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("owner"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    UNAUTHORIZED
+                },
+                default_assignment: Some(fname!("owner")),
             }
         },
         extensions: none!(),
@@ -177,11 +482,134 @@ pub fn rgb_e721() -> Iface {
     }
 }
 
+/// ERC-5192 `locked`: a token is soulbound if the whole collection is locked
+/// or its allocation is assigned as `locked_beneficiary`.
+pub fn is_locked(
+    collection_locked: bool,
+    locked_tokens: impl IntoIterator<Item = U256>,
+    token_id: U256,
+) -> bool {
+    collection_locked || locked_tokens.into_iter().any(|id| id == token_id)
+}
+
+pub fn rgb_e721_royalty() -> Iface {
+    let types = StandardTypes::with(rgb_e721_stl());
+
+    Iface {
+        version: VerNo::V1,
+        name: tn!("RGB-E721Royalty"),
+        global_state: tiny_bmap! {
+            fname!("royalty") => GlobalIface::required(types.get("RGB-E721.Royalty")),
+            fname!("token_royalties") => GlobalIface::none_or_many(types.get("RGB-E721.TokenRoyalty")),
+        },
+        genesis: GenesisIface {
+            metadata: None,
+            global: tiny_bmap! {
+                fname!("royalty") => ArgSpec::required(),
+                fname!("token_royalties") => ArgSpec::many(),
+            },
+            assignments: none!(),
+            valencies: none!(),
+            errors: tiny_bset! {
+                INVALID_ROYALTY
+            },
+        },
+        transitions: tiny_bmap! {
+            tn!("RoyaltyInfo") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("token_id") => ArgSpec::from_required("Index"),
+                    fname!("sale_price") => ArgSpec::from_required("Amount"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    INVALID_ROYALTY,
+                    AMOUNT_OVERFLOW
+                },
+                default_assignment: None,
+            },
+        },
+        extensions: none!(),
+        error_type: types.get("RGB-E721.Error"),
+        default_operation: None,
+    }
+}
+
+pub fn rgb_e721_rental() -> Iface {
+    let types = StandardTypes::with(rgb_e721_stl());
+
+    Iface {
+        version: VerNo::V1,
+        name: tn!("RGB-E721Rental"),
+        global_state: none!(),
+        assignments: tiny_bmap! {
+            fname!("user_right") => AssignIface::public(OwnedIface::Data(types.get("RGB-E721.UserRight")), Req::NoneOrMore),
+        },
+        genesis: GenesisIface {
+            metadata: None,
+            global: none!(),
+            assignments: none!(),
+            valencies: none!(),
+            errors: none!(),
+        },
+        transitions: tiny_bmap! {
+            tn!("SetUser") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: tiny_bmap! {
+                    fname!("timestamp") => ArgSpec::required(),
+                },
+                inputs: tiny_bmap! {
+                    fname!("token_id") => ArgSpec::from_required("Index"),
+                    fname!("user") => ArgSpec::from_required("Account"),
+                    fname!("expires") => ArgSpec::from_required("timestamp"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    TOKEN_NOT_EXIST,
+                    INVALID_PROOF,
+                    UNAUTHORIZED,
+                    PAUSED
+                },
+                default_assignment: Some(fname!("user_right")),
+            },
+            tn!("UserOf") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("token_id") => ArgSpec::from_required("Index"),
+                },
+                valencies: none!(),
+                errors: none!(),
+                default_assignment: None,
+            },
+            tn!("UserExpires") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("token_id") => ArgSpec::from_required("Index"),
+                },
+                valencies: none!(),
+                errors: none!(),
+                default_assignment: None,
+            },
+        },
+        extensions: none!(),
+        error_type: types.get("RGB-E721.Error"),
+        default_operation: Some(tn!("SetUser")),
+    }
+}
+
 pub trait RgbE721Iface {
     //Genesis
     fn init(&self, name: &str, symbol: &str);
     //Transaction
     fn owner_of(token_id: U256) -> Account;
+    fn owner_of_confirmed(token_id: U256, min_depth: u32) -> Account;
     fn safe_transfer_from(from: Account,to: Account,token_id: U256);
     fn transfer_from(from: Account,to: Account,token_id: U256);
     fn approve(approved: Account,token_id: U256) -> Account;
@@ -192,7 +620,29 @@ pub trait RgbE721Iface {
     fn name() -> String;
     fn symbol() -> String;
     fn token_uri(token_id:U256) -> String;
+    fn set_base_uri(base_uri: &str);
     fn total_supply() -> U256;
     fn token_by_index(index: U256) -> U256;
     fn token_of_owner_by_index(owner: Account,index: U256) -> U256;
+    fn locked(token_id: U256) -> bool;
+    fn paused() -> bool;
+    //Mintable
+    fn mint(to: Account, token_id: U256, data: TokenData);
+    //Burnable
+    fn burn(token_id: U256);
+    //Pausable
+    fn pause();
+    fn unpause();
+    //Introspection
+    fn supports_interface(interface_id: [u8; 4]) -> bool;
+}
+
+pub trait RgbE721RoyaltyIface: RgbE721Iface {
+    fn royalty_info(token_id: U256, sale_price: U256) -> (Account, U256);
+}
+
+pub trait RgbE721RentalIface: RgbE721Iface {
+    fn set_user(token_id: U256, user: Account, expires: i64);
+    fn user_of(token_id: U256) -> Option<Account>;
+    fn user_expires(token_id: U256) -> i64;
 }
@@ -1,13 +1,36 @@
 ///RGB-E721 SCHEMA
-use rgbstd::interface::{rgb_e721, rgb_e721_stl, IfaceImpl, NamedField, NamedType, VerNo};
+use aluvm::data::ByteStr;
+use aluvm::isa::{ArithmeticOp, BitwiseOp, BytesOp, CmpOp, ControlFlowOp, Instr, MovOp, PutOp};
+use aluvm::library::{Lib, LibSite};
+use aluvm::reg::{Reg16, Reg32, RegA, RegR, RegS};
+use rgbstd::interface::rgb_e721::{Error, ROYALTY_BPS_DENOMINATOR};
+use rgbstd::interface::{
+    rgb_e721, rgb_e721_rental, rgb_e721_royalty, rgb_e721_stl, IfaceImpl, NamedField, NamedType,
+    VerNo,
+};
 use rgbstd::schema::{
     GenesisSchema, GlobalStateSchema, Occurrences, Schema, Script, StateSchema, SubSchema,
     TransitionSchema,
 };
 use rgbstd::stl::StandardTypes;
-use rgbstd::vm::AluScript;
+use rgbstd::vm::{AluScript, ContractOp, EntryPoint, RgbIsa};
 use strict_types::{SemId, Ty};
 
+use crate::rgb_access_control_schema::{
+    access_control_impl, grant_role_schema, has_role_schema, renounce_role_schema, revoke_role_lib,
+    revoke_role_schema, OS_ROLE_ADMIN, OS_ROLE_BURNER, OS_ROLE_MINTER, OS_ROLE_PAUSER, ROLE_RIGHT,
+    TS_GRANT_ROLE, TS_HAS_ROLE, TS_RENOUNCE_ROLE, TS_REVOKE_ROLE,
+};
+use crate::rgb_alu::{
+    all_of, any_inputs, code_len, counts_match, errno, flag_unset, globals_at_most, ids_increase,
+    ids_spent, no_inputs, reassigns_unchanged, sets_flag, spent_count_matches, supply_grows,
+    supply_shrinks, Check,
+};
+use crate::rgb_ownable_schema::{
+    ownable_impl, owner_schema, ownership_lib, renounce_ownership_schema,
+    transfer_ownership_schema, OS_OWNER_RIGHT, TS_OWNER, TS_RENOUNCE_OWNERSHIP,
+    TS_TRANSFER_OWNERSHIP,
+};
 use crate::GS_TIMESTAMP;
 
 const GS_NOMINAL: u16 = 72100;
@@ -16,12 +39,185 @@ const GS_TOKENS: u16 = 72102;
 #[allow(dead_code)]
 const GS_ENGRAVINGS: u16 = 72103;
 const GS_ATTACH: u16 = 72104;
-const OS_ASSET: u16 = 72100;
+const GS_ROYALTY: u16 = 72105;
+const GS_TOKEN_ROYALTIES: u16 = 72106;
+const GS_LOCKED: u16 = 72107;
+const GS_PAUSED: u16 = 72108;
+const GS_BASE_URI: u16 = 72109;
+const GS_BURNED: u16 = 72110;
+const GS_APPROVED: u16 = 72111;
+pub const OS_ASSET: u16 = 72100;
+pub const OS_LOCKED_ASSET: u16 = 72101;
+const OS_USER_RIGHT: u16 = 72102;
+const OS_APPROVAL: u16 = 72103;
+const OS_OPERATOR: u16 = 72104;
 const TS_TRANSFER: u16 = 72100;
+const TS_ROYALTY_INFO: u16 = 72101;
+const TS_LOCKED: u16 = 72102;
+const TS_SET_USER: u16 = 72103;
+const TS_USER_OF: u16 = 72104;
+const TS_USER_EXPIRES: u16 = 72105;
+const TS_PAUSE: u16 = 72106;
+const TS_UNPAUSE: u16 = 72107;
+const TS_SET_BASE_URI: u16 = 72108;
+const TS_MINT: u16 = 72109;
+const TS_BURN: u16 = 72110;
+
+/// Genesis of a locked collection may only assign `OS_LOCKED_ASSET`:
+/// fails unless `count(GS_LOCKED) * count(OS_ASSET) == 0`.
+fn locked_genesis() -> Check {
+    vec![
+        errno(Error::TokenLocked as u8),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnG(GS_LOCKED, Reg16::Reg0))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnS(OS_ASSET, Reg16::Reg1))),
+        Instr::Arithmetic(ArithmeticOp::Mul(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg1)),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg2, Box::new(0u16.into()))),
+        Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg1, Reg32::Reg2)),
+    ]
+}
+
+/// Mints into a locked collection may only assign `OS_LOCKED_ASSET`, and
+/// mints into an unlocked one only `OS_ASSET`. As in genesis, a collection is
+/// locked if it has a `GS_LOCKED` value.
+fn locked_mint() -> Check {
+    let mut check = vec![
+        errno(Error::TokenLocked as u8),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnC(GS_LOCKED, Reg16::Reg0))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnS(OS_ASSET, Reg16::Reg1))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::CnS(OS_LOCKED_ASSET, Reg16::Reg2))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg3, Box::new(0u16.into()))),
+        Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg0, Reg32::Reg3)),
+    ];
+    let jif_unlocked = check.len();
+    check.push(Instr::ControlFlow(ControlFlowOp::Jif(0)));
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg1, Reg32::Reg3)));
+    let jmp_end = check.len();
+    check.push(Instr::ControlFlow(ControlFlowOp::Jmp(0)));
+    let unlocked = code_len(&check);
+    check.push(Instr::Cmp(CmpOp::EqA(none!(), RegA::A16, Reg32::Reg2, Reg32::Reg3)));
+    check[jif_unlocked] = Instr::ControlFlow(ControlFlowOp::Jif(unlocked));
+    check[jmp_end] = Instr::ControlFlow(ControlFlowOp::Jmp(code_len(&check)));
+    check
+}
+
+/// The state of type `ty` assigned by the operation must be for the token it
+/// spends: its leading 256-bit token id, e.g. `UserRight.token_id` or
+/// `Approval.token_id`, must equal the index of the spent `Allocation`. The
+/// 12-byte allocation is zero-padded to read its leading `u32` index as a
+/// 256-bit number.
+fn matches_token(ty: u16) -> Check {
+    vec![
+        errno(Error::NonEqualAmounts as u8),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg3, Box::new(0u16.into()))),
+        Instr::Put(PutOp::PutA(RegA::A256, Reg32::Reg3, Box::new(u32::MAX.into()))),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdP(OS_ASSET, 0, RegS::from(0)))),
+        Instr::Bytes(BytesOp::Put(RegS::from(5), Box::new(ByteStr::with([0u8; 20])), false)),
+        Instr::Bytes(BytesOp::Join(RegS::from(0), RegS::from(5), RegS::from(6))),
+        Instr::Bytes(BytesOp::Extr(RegS::from(6), RegR::R256, Reg16::Reg0, Reg16::Reg3)),
+        Instr::Move(MovOp::SpyAR(RegA::A256, Reg32::Reg0, RegR::R256, Reg32::Reg0)),
+        Instr::Bitwise(BitwiseOp::And(RegA::A256.into(), Reg32::Reg0, Reg32::Reg3, Reg32::Reg0)),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdS(ty, 0, RegS::from(1)))),
+        Instr::Bytes(BytesOp::Extr(RegS::from(1), RegR::R256, Reg16::Reg1, Reg16::Reg3)),
+        Instr::Move(MovOp::SpyAR(RegA::A256, Reg32::Reg1, RegR::R256, Reg32::Reg1)),
+        Instr::Cmp(CmpOp::EqA(none!(), RegA::A256, Reg32::Reg0, Reg32::Reg1)),
+    ]
+}
+
+/// The user right assigned by `SetUser` must record the timestamp the
+/// transition defines as its `since`, which ends the 68-byte `UserRight`.
+/// Both values are zero-padded to compare the 64-bit timestamps as 128-bit
+/// numbers.
+fn user_right_since() -> Check {
+    let pad = ByteStr::with([0u8; 8]);
+    vec![
+        errno(Error::InvalidProof as u8),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg3, Box::new(0u16.into()))),
+        Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg4, Box::new(60u16.into()))),
+        Instr::Bytes(BytesOp::Put(RegS::from(5), Box::new(pad), false)),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdG(GS_TIMESTAMP, 0, RegS::from(0)))),
+        Instr::Bytes(BytesOp::Join(RegS::from(0), RegS::from(5), RegS::from(0))),
+        Instr::Bytes(BytesOp::Extr(RegS::from(0), RegR::R128, Reg16::Reg0, Reg16::Reg3)),
+        Instr::ExtensionCodes(RgbIsa::Contract(ContractOp::LdS(OS_USER_RIGHT, 0, RegS::from(1)))),
+        Instr::Bytes(BytesOp::Join(RegS::from(1), RegS::from(5), RegS::from(1))),
+        Instr::Bytes(BytesOp::Extr(RegS::from(1), RegR::R128, Reg16::Reg1, Reg16::Reg4)),
+        Instr::Cmp(CmpOp::EqR(none!(), RegR::R128, Reg32::Reg0, Reg32::Reg1)),
+    ]
+}
+
+fn transfer_lib() -> Lib {
+    all_of([
+        no_inputs(OS_LOCKED_ASSET, Error::TokenLocked as u8),
+        flag_unset(GS_PAUSED, Error::Paused as u8),
+    ])
+}
 
 pub fn uda_schema() -> SubSchema {
-    let types = StandardTypes::with(rgb721_stl());
+    let types = StandardTypes::with(rgb_e721_stl());
 
+    // basis points lead `Royalty`, which follows the token id in `TokenRoyalty`
+    let bps = ROYALTY_BPS_DENOMINATOR as u64;
+    let genesis_lib = all_of([
+        locked_genesis(),
+        ids_increase(GS_TOKENS, Error::TokenExists as u8),
+        supply_grows(GS_TOTAL_SUPPLY, GS_TOKENS, Error::SupplyMismatch as u8),
+        globals_at_most(GS_ROYALTY, 0, 16, bps, Error::InvalidRoyalty as u8),
+        globals_at_most(GS_TOKEN_ROYALTIES, 32, 16, bps, Error::InvalidRoyalty as u8),
+    ]);
+    let genesis_id = genesis_lib.id();
+    let transfer_lib = transfer_lib();
+    let transfer_id = transfer_lib.id();
+    let mint_lib = all_of([
+        any_inputs(&[OS_ROLE_MINTER, OS_OWNER_RIGHT], Error::Unauthorized as u8),
+        counts_match(GS_TOKENS, &[OS_ASSET, OS_LOCKED_ASSET], Error::SupplyMismatch as u8),
+        ids_increase(GS_TOKENS, Error::TokenExists as u8),
+        supply_grows(GS_TOTAL_SUPPLY, GS_TOKENS, Error::SupplyMismatch as u8),
+        locked_mint(),
+        flag_unset(GS_PAUSED, Error::Paused as u8),
+    ]);
+    let mint_id = mint_lib.id();
+    let burn_lib = all_of([
+        any_inputs(&[OS_ROLE_BURNER, OS_OWNER_RIGHT], Error::Unauthorized as u8),
+        spent_count_matches(GS_BURNED, &[OS_ASSET, OS_LOCKED_ASSET], Error::SupplyMismatch as u8),
+        ids_spent(GS_BURNED, &[OS_ASSET, OS_LOCKED_ASSET], Error::NonEqualAmounts as u8),
+        supply_shrinks(GS_TOTAL_SUPPLY, GS_BURNED, Error::SupplyMismatch as u8),
+        flag_unset(GS_PAUSED, Error::Paused as u8),
+    ]);
+    let burn_id = burn_lib.id();
+    // approvals authenticate with the owner's allocations, which must stay put
+    let approve_lib = all_of([
+        reassigns_unchanged(OS_ASSET, Error::Unauthorized as u8),
+        matches_token(OS_APPROVAL),
+        flag_unset(GS_PAUSED, Error::Paused as u8),
+    ]);
+    let approve_id = approve_lib.id();
+    let approve_all_lib = all_of([
+        reassigns_unchanged(OS_ASSET, Error::Unauthorized as u8),
+        flag_unset(GS_PAUSED, Error::Paused as u8),
+    ]);
+    let approve_all_id = approve_all_lib.id();
+    // the token goes back unchanged to a seal picked by its owner, who spends
+    // it; seals are not visible to the script
+    let set_user_lib = all_of([
+        reassigns_unchanged(OS_ASSET, Error::Unauthorized as u8),
+        matches_token(OS_USER_RIGHT),
+        user_right_since(),
+        flag_unset(GS_PAUSED, Error::Paused as u8),
+    ]);
+    let set_user_id = set_user_lib.id();
+    let pause_lib = all_of([
+        any_inputs(&[OS_ROLE_PAUSER, OS_OWNER_RIGHT], Error::Unauthorized as u8),
+        sets_flag(GS_PAUSED, true, Error::InvalidProof as u8),
+    ]);
+    let pause_id = pause_lib.id();
+    let unpause_lib = all_of([
+        any_inputs(&[OS_ROLE_PAUSER, OS_OWNER_RIGHT], Error::Unauthorized as u8),
+        sets_flag(GS_PAUSED, false, Error::InvalidProof as u8),
+    ]);
+    let unpause_id = unpause_lib.id();
+    let revoke_role_lib = revoke_role_lib();
+    let revoke_role_id = revoke_role_lib.id();
+    let ownership_lib = ownership_lib();
+    let ownership_id = ownership_lib.id();
     Schema {
         ffv: zero!(),
         subset_of: None,
@@ -29,22 +225,53 @@ pub fn uda_schema() -> SubSchema {
         global_types: tiny_bmap! {
            GS_NAME => GlobalStateSchema::once(types.get("RGBContract.Text")),
             GS_SYMBOL => GlobalStateSchema::once(types.get("RGBContract.Text")),
-            GS_TOTAL_SUPPLY => GlobalStateSchema::once(types.get("RGBContract.amount")),
+            GS_TOTAL_SUPPLY => GlobalStateSchema::many(types.get("RGBContract.amount")),
             GS_TOKEN_BY_INDEX => GlobalStateSchema::once(types.get("RGBContract.amount")),
             GS_TOKEN_OF_OWNER_BY_INDEX => GlobalStateSchema::once(types.get("RGBContract.amount")),
+            GS_TOKENS => GlobalStateSchema::many(types.get("RGBContract.TokenData")),
+            GS_ROYALTY => GlobalStateSchema::once(types.get("RGB-E721.Royalty")),
+            GS_TOKEN_ROYALTIES => GlobalStateSchema::many(types.get("RGB-E721.TokenRoyalty")),
+            GS_LOCKED => GlobalStateSchema::once(types.get("Std.Bool")),
+            GS_TIMESTAMP => GlobalStateSchema::once(types.get("RGBContract.Timestamp")),
+            GS_PAUSED => GlobalStateSchema::many(types.get("Std.Bool")),
+            GS_BASE_URI => GlobalStateSchema::many(types.get("RGBContract.Details")),
+            GS_BURNED => GlobalStateSchema::many(types.get("RGBContract.amount")),
+            GS_APPROVED => GlobalStateSchema::many(types.get("Std.Bool")),
 
         },
         owned_types: tiny_bmap! {
             OS_ASSET => StateSchema::Structured(types.get("RGB_E721.Allocation")),
+            OS_LOCKED_ASSET => StateSchema::Structured(types.get("RGB_E721.Allocation")),
+            OS_USER_RIGHT => StateSchema::Structured(types.get("RGB-E721.UserRight")),
+            OS_APPROVAL => StateSchema::Structured(types.get("RGB-E721.Approval")),
+            OS_OPERATOR => StateSchema::Declarative,
+            OS_ROLE_ADMIN => ROLE_RIGHT,
+            OS_ROLE_MINTER => ROLE_RIGHT,
+            OS_ROLE_BURNER => ROLE_RIGHT,
+            OS_ROLE_PAUSER => ROLE_RIGHT,
+            OS_OWNER_RIGHT => StateSchema::Structured(types.get("RGB-Ownable.OwnerRight")),
         },
         valency_types: none!(),
         genesis: GenesisSchema {
             metadata: Ty::<SemId>::UNIT.id(None),
             globals: tiny_bmap! {
                 GS_INIT => Occurrences::Once,
+                GS_TOTAL_SUPPLY => Occurrences::Once,
+                GS_TOKENS => Occurrences::NoneOrMore,
+                GS_ROYALTY => Occurrences::NoneOrOnce,
+                GS_TOKEN_ROYALTIES => Occurrences::NoneOrMore,
+                GS_LOCKED => Occurrences::NoneOrOnce,
+                GS_PAUSED => Occurrences::Once,
+                GS_BASE_URI => Occurrences::NoneOrOnce,
             },
             assignments: tiny_bmap! {
-                OS_ASSET => Occurrences::Once,
+                OS_ASSET => Occurrences::NoneOrOnce,
+                OS_LOCKED_ASSET => Occurrences::NoneOrOnce,
+                OS_ROLE_ADMIN => Occurrences::Once,
+                OS_ROLE_MINTER => Occurrences::NoneOrMore,
+                OS_ROLE_BURNER => Occurrences::NoneOrMore,
+                OS_ROLE_PAUSER => Occurrences::NoneOrMore,
+                OS_OWNER_RIGHT => Occurrences::Once,
             },
             valencies: none!(),
         },
@@ -62,9 +289,11 @@ pub fn uda_schema() -> SubSchema {
             TS_SAFE_TRANSFER_FROM => TransitionSchema {
                 metadata: Ty::<SemId>::UNIT.id(None),
                 globals: none!(),
-                inputs: none!(),
+                inputs: tiny_bmap! {
+                    OS_ASSET => Occurrences::OnceOrMore
+                },
                 assignments: tiny_bmap! {
-                    OS_ASSETS => Occurrences::OnceOrMore
+                    OS_ASSET => Occurrences::OnceOrMore
                 },
                 valencies: none!(),
             },
@@ -83,19 +312,25 @@ pub fn uda_schema() -> SubSchema {
                 metadata: Ty::<SemId>::UNIT.id(None),
                 globals: none!(),
                 inputs: tiny_bmap! {
-                    OS_ASSETS => Occurrences::OnceOrMore
+                    OS_ASSET => Occurrences::Once
+                },
+                assignments: tiny_bmap! {
+                    OS_ASSET => Occurrences::Once,
+                    OS_APPROVAL => Occurrences::Once
                 },
-                assignments: none!(),
                 valencies: none!(),
             },
              TS_SET_APPROVED_FOR_ALL => TransitionSchema {
                 metadata: Ty::<SemId>::UNIT.id(None),
-                globals: none!(),
+                globals: tiny_bmap! {
+                    GS_APPROVED => Occurrences::Once
+                },
                 inputs: tiny_bmap! {
-                    OS_ASSETS => Occurrences::OnceOrMore
+                    OS_ASSET => Occurrences::OnceOrMore
                 },
                 assignments: tiny_bmap! {
-                    OS_ASSETS => Occurrences::OnceOrMore
+                    OS_ASSET => Occurrences::OnceOrMore,
+                    OS_OPERATOR => Occurrences::NoneOrOnce
                 },
                 valencies: none!(),
             },
@@ -118,11 +353,165 @@ pub fn uda_schema() -> SubSchema {
                     OS_ASSETS => Occurrences::OnceOrMore
                 },
                 valencies: none!(),
-            }
+            },
+            TS_ROYALTY_INFO => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: none!(),
+                assignments: none!(),
+                valencies: none!(),
+            },
+            TS_LOCKED => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: none!(),
+                assignments: none!(),
+                valencies: none!(),
+            },
+            TS_SET_USER => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: tiny_bmap! {
+                    GS_TIMESTAMP => Occurrences::Once
+                },
+                inputs: tiny_bmap! {
+                    OS_ASSET => Occurrences::Once
+                },
+                assignments: tiny_bmap! {
+                    OS_ASSET => Occurrences::Once,
+                    OS_USER_RIGHT => Occurrences::Once
+                },
+                valencies: none!(),
+            },
+            TS_USER_OF => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: none!(),
+                assignments: none!(),
+                valencies: none!(),
+            },
+            TS_USER_EXPIRES => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: none!(),
+                inputs: none!(),
+                assignments: none!(),
+                valencies: none!(),
+            },
+            TS_MINT => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: tiny_bmap! {
+                    GS_TOKENS => Occurrences::OnceOrMore,
+                    GS_TOTAL_SUPPLY => Occurrences::Once
+                },
+                inputs: tiny_bmap! {
+                    OS_ROLE_MINTER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_ASSET => Occurrences::NoneOrMore,
+                    OS_LOCKED_ASSET => Occurrences::NoneOrMore,
+                    OS_ROLE_MINTER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                valencies: none!(),
+            },
+            TS_BURN => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: tiny_bmap! {
+                    GS_BURNED => Occurrences::OnceOrMore,
+                    GS_TOTAL_SUPPLY => Occurrences::Once
+                },
+                inputs: tiny_bmap! {
+                    OS_ASSET => Occurrences::NoneOrMore,
+                    OS_LOCKED_ASSET => Occurrences::NoneOrMore,
+                    OS_ROLE_BURNER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_ROLE_BURNER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                valencies: none!(),
+            },
+            TS_PAUSE => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: tiny_bmap! {
+                    GS_PAUSED => Occurrences::Once
+                },
+                inputs: tiny_bmap! {
+                    OS_ROLE_PAUSER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_ROLE_PAUSER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                valencies: none!(),
+            },
+            TS_UNPAUSE => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: tiny_bmap! {
+                    GS_PAUSED => Occurrences::Once
+                },
+                inputs: tiny_bmap! {
+                    OS_ROLE_PAUSER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                assignments: tiny_bmap! {
+                    OS_ROLE_PAUSER => Occurrences::NoneOrOnce,
+                    OS_OWNER_RIGHT => Occurrences::NoneOrOnce
+                },
+                valencies: none!(),
+            },
+            TS_SET_BASE_URI => TransitionSchema {
+                metadata: Ty::<SemId>::UNIT.id(None),
+                globals: tiny_bmap! {
+                    GS_BASE_URI => Occurrences::Once
+                },
+                inputs: tiny_bmap! {
+                    OS_OWNER_RIGHT => Occurrences::Once
+                },
+                assignments: tiny_bmap! {
+                    OS_OWNER_RIGHT => Occurrences::Once
+                },
+                valencies: none!(),
+            },
+            TS_GRANT_ROLE => grant_role_schema(),
+            TS_REVOKE_ROLE => revoke_role_schema(),
+            TS_RENOUNCE_ROLE => renounce_role_schema(),
+            TS_HAS_ROLE => has_role_schema(),
+            TS_TRANSFER_OWNERSHIP => transfer_ownership_schema(),
+            TS_RENOUNCE_OWNERSHIP => renounce_ownership_schema(),
+            TS_OWNER => owner_schema()
         },
         script: Script::AluVM(AluScript {
-            libs: none!(),
-            entry_points: none!(),
+            libs: confined_bmap! {
+                genesis_id => genesis_lib,
+                transfer_id => transfer_lib,
+                mint_id => mint_lib,
+                burn_id => burn_lib,
+                approve_id => approve_lib,
+                approve_all_id => approve_all_lib,
+                set_user_id => set_user_lib,
+                pause_id => pause_lib,
+                unpause_id => unpause_lib,
+                revoke_role_id => revoke_role_lib,
+                ownership_id => ownership_lib,
+            },
+            entry_points: confined_bmap! {
+                EntryPoint::ValidateGenesis => LibSite::with(0, genesis_id),
+                EntryPoint::ValidateTransition(TS_TRANSFER_FROM) => LibSite::with(0, transfer_id),
+                EntryPoint::ValidateTransition(TS_SAFE_TRANSFER_FROM) => LibSite::with(0, transfer_id),
+                EntryPoint::ValidateTransition(TS_MINT) => LibSite::with(0, mint_id),
+                EntryPoint::ValidateTransition(TS_BURN) => LibSite::with(0, burn_id),
+                EntryPoint::ValidateTransition(TS_APPROVE) => LibSite::with(0, approve_id),
+                EntryPoint::ValidateTransition(TS_SET_APPROVED_FOR_ALL) => LibSite::with(0, approve_all_id),
+                EntryPoint::ValidateTransition(TS_SET_USER) => LibSite::with(0, set_user_id),
+                EntryPoint::ValidateTransition(TS_PAUSE) => LibSite::with(0, pause_id),
+                EntryPoint::ValidateTransition(TS_UNPAUSE) => LibSite::with(0, unpause_id),
+                EntryPoint::ValidateTransition(TS_REVOKE_ROLE) => LibSite::with(0, revoke_role_id),
+                EntryPoint::ValidateTransition(TS_TRANSFER_OWNERSHIP) => LibSite::with(0, ownership_id),
+                EntryPoint::ValidateTransition(TS_RENOUNCE_OWNERSHIP) => LibSite::with(0, ownership_id),
+            },
         }),
     }
 }
@@ -141,9 +530,23 @@ pub fn uda_rgb721() -> IfaceImpl {
             NamedField::with(GS_TOTAL_SUPPLY, fname!("total_supply")),
             NamedField::with(GS_TOKEN_BY_INDEX, fname!("token_by_index")),
             NamedField::with(GS_TOKEN_OF_OWNER_BY_INDEX, fname!("token_of_owner_by_index")),
+            NamedField::with(GS_TOKENS, fname!("tokens")),
+            NamedField::with(GS_LOCKED, fname!("locked")),
+            NamedField::with(GS_PAUSED, fname!("paused")),
+            NamedField::with(GS_BASE_URI, fname!("base_uri")),
+            NamedField::with(GS_BURNED, fname!("burned")),
+            NamedField::with(GS_APPROVED, fname!("approved")),
         },
         assignments: tiny_bset! {
             NamedField::with(OS_ASSET, fname!("beneficiary")),
+            NamedField::with(OS_LOCKED_ASSET, fname!("locked_beneficiary")),
+            NamedField::with(OS_ROLE_ADMIN, fname!("admin_role")),
+            NamedField::with(OS_ROLE_MINTER, fname!("minter_role")),
+            NamedField::with(OS_ROLE_BURNER, fname!("burner_role")),
+            NamedField::with(OS_ROLE_PAUSER, fname!("pauser_role")),
+            NamedField::with(OS_OWNER_RIGHT, fname!("owner")),
+            NamedField::with(OS_APPROVAL, fname!("approval")),
+            NamedField::with(OS_OPERATOR, fname!("operator")),
         },
         valencies: none!(),
         transitions: tiny_bset! {
@@ -154,6 +557,59 @@ pub fn uda_rgb721() -> IfaceImpl {
             NamedType::with(TS_SET_APPROVED_FOR_ALL, tn!("set_approve_for_all")),
             NamedType::with(TS_GET_APPROVED, tn!("get_approved")),
             NamedType::with(TS_IS_APPROVED_FOR_ALL, tn!("is_approved_for_all")),
+            NamedType::with(TS_LOCKED, tn!("locked")),
+            NamedType::with(TS_MINT, tn!("mint")),
+            NamedType::with(TS_BURN, tn!("burn")),
+            NamedType::with(TS_PAUSE, tn!("pause")),
+            NamedType::with(TS_UNPAUSE, tn!("unpause")),
+            NamedType::with(TS_SET_BASE_URI, tn!("set_base_uri")),
             extensions: none!(),
     }
 }
+
+pub fn uda_rgb721_royalty() -> IfaceImpl {
+    let schema = uda_schema();
+    let iface = rgb_e721_royalty();
+
+    IfaceImpl {
+        version: VerNo::V1,
+        schema_id: schema.schema_id(),
+        iface_id: iface.iface_id(),
+        global_state: tiny_bset! {
+            NamedField::with(GS_ROYALTY, fname!("royalty")),
+            NamedField::with(GS_TOKEN_ROYALTIES, fname!("token_royalties")),
+        },
+        assignments: none!(),
+        valencies: none!(),
+        transitions: tiny_bset! {
+            NamedType::with(TS_ROYALTY_INFO, tn!("royalty_info")),
+        },
+        extensions: none!(),
+    }
+}
+
+pub fn uda_rgb721_rental() -> IfaceImpl {
+    let schema = uda_schema();
+    let iface = rgb_e721_rental();
+
+    IfaceImpl {
+        version: VerNo::V1,
+        schema_id: schema.schema_id(),
+        iface_id: iface.iface_id(),
+        global_state: none!(),
+        assignments: tiny_bset! {
+            NamedField::with(OS_USER_RIGHT, fname!("user_right")),
+        },
+        valencies: none!(),
+        transitions: tiny_bset! {
+            NamedType::with(TS_SET_USER, tn!("set_user")),
+            NamedType::with(TS_USER_OF, tn!("user_of")),
+            NamedType::with(TS_USER_EXPIRES, tn!("user_expires")),
+        },
+        extensions: none!(),
+    }
+}
+
+pub fn uda_access_control() -> IfaceImpl { access_control_impl(uda_schema().schema_id()) }
+
+pub fn uda_ownable() -> IfaceImpl { ownable_impl(uda_schema().schema_id()) }
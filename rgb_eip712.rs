@@ -0,0 +1,318 @@
+//EIP-712 typed structured data
+use rgbstd::contract::ContractId;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use sha3::{Digest, Keccak256};
+use strict_types::TypeName;
+
+pub type Hash = [u8; 32];
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum Eip712Error {
+    /// signature recovery id {0} is invalid.
+    InvalidRecoveryId(u8),
+    /// signature is not a valid secp256k1 signature.
+    InvalidSignature,
+    /// signature was produced by {found} instead of {expected}.
+    WrongSigner { expected: Account, found: Account },
+}
+
+pub fn keccak(data: &[u8]) -> Hash { Keccak256::digest(data).into() }
+
+pub fn encode_u64(value: u64) -> Hash {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+pub fn encode_u256(value: U256) -> Hash { value.to_be_bytes() }
+
+pub fn encode_bool(value: bool) -> Hash { encode_u64(value as u64) }
+
+pub fn encode_address(account: &Account) -> Hash {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&account.to_byte_array());
+    word
+}
+
+pub fn encode_string(value: &str) -> Hash { keccak(value.as_bytes()) }
+
+pub fn encode_bytes(value: &[u8]) -> Hash { keccak(value) }
+
+pub fn account_from_pubkey(pubkey: &PublicKey) -> Account {
+    let hash = keccak(&pubkey.serialize_uncompressed()[1..]);
+    let mut addr = [0u8; 20];
+    addr.copy_from_slice(&hash[12..]);
+    Account::from(addr)
+}
+
+/// A struct which can be hashed according to EIP-712 `hashStruct`.
+pub trait Eip712Struct {
+    /// `encodeType` of the struct, including all referenced struct types
+    /// sorted by name, e.g. `Mail(Person from,Person to,string contents)Person(string name,address wallet)`.
+    const ENCODED_TYPE: &'static str;
+
+    /// `encodeData` of the struct members, one 32-byte word per member.
+    fn encode_data(&self) -> Vec<Hash>;
+
+    fn type_hash() -> Hash { keccak(Self::ENCODED_TYPE.as_bytes()) }
+
+    fn struct_hash(&self) -> Hash {
+        let members = self.encode_data();
+        let mut data = Vec::with_capacity(32 * (members.len() + 1));
+        data.extend(Self::type_hash());
+        members.iter().for_each(|word| data.extend(word));
+        keccak(&data)
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Eip712Domain {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub chain_id: Option<U256>,
+    pub verifying_contract: Option<Account>,
+    pub salt: Option<Hash>,
+}
+
+impl Eip712Domain {
+    /// Domain of an RGB contract: the interface name as domain name and the
+    /// contract id as salt, since RGB contracts have no chain id or address.
+    pub fn with_contract(contract_id: ContractId, iface: &TypeName) -> Self {
+        Eip712Domain {
+            name: Some(iface.to_string()),
+            version: Some(s!("1")),
+            chain_id: None,
+            verifying_contract: None,
+            salt: Some(contract_id.to_byte_array()),
+        }
+    }
+
+    pub fn encoded_type(&self) -> String {
+        let fields = [
+            self.name.as_ref().map(|_| "string name"),
+            self.version.as_ref().map(|_| "string version"),
+            self.chain_id.as_ref().map(|_| "uint256 chainId"),
+            self.verifying_contract.as_ref().map(|_| "address verifyingContract"),
+            self.salt.as_ref().map(|_| "bytes32 salt"),
+        ];
+        let fields = fields.into_iter().flatten().collect::<Vec<_>>().join(",");
+        format!("EIP712Domain({fields})")
+    }
+
+    pub fn separator(&self) -> Hash {
+        let mut data = Vec::with_capacity(6 * 32);
+        data.extend(keccak(self.encoded_type().as_bytes()));
+        if let Some(name) = &self.name {
+            data.extend(encode_string(name));
+        }
+        if let Some(version) = &self.version {
+            data.extend(encode_string(version));
+        }
+        if let Some(chain_id) = self.chain_id {
+            data.extend(encode_u256(chain_id));
+        }
+        if let Some(contract) = &self.verifying_contract {
+            data.extend(encode_address(contract));
+        }
+        if let Some(salt) = self.salt {
+            data.extend(salt);
+        }
+        keccak(&data)
+    }
+
+    /// `keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(message))`.
+    pub fn signing_hash(&self, message: &impl Eip712Struct) -> Hash {
+        let mut data = Vec::with_capacity(2 + 2 * 32);
+        data.extend([0x19, 0x01]);
+        data.extend(self.separator());
+        data.extend(message.struct_hash());
+        keccak(&data)
+    }
+
+    pub fn sign(&self, message: &impl Eip712Struct, key: &SecretKey) -> Eip712Signature {
+        let msg = Message::from_slice(&self.signing_hash(message)).expect("32-byte hash");
+        let (recid, compact) = Secp256k1::signing_only()
+            .sign_ecdsa_recoverable(&msg, key)
+            .serialize_compact();
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&compact[..32]);
+        s.copy_from_slice(&compact[32..]);
+        Eip712Signature {
+            v: 27 + recid.to_i32() as u8,
+            r,
+            s,
+        }
+    }
+
+    pub fn recover(
+        &self,
+        message: &impl Eip712Struct,
+        signature: &Eip712Signature,
+    ) -> Result<Account, Eip712Error> {
+        let recid = signature
+            .v
+            .checked_sub(27)
+            .and_then(|v| RecoveryId::from_i32(v as i32).ok())
+            .ok_or(Eip712Error::InvalidRecoveryId(signature.v))?;
+        let mut compact = [0u8; 64];
+        compact[..32].copy_from_slice(&signature.r);
+        compact[32..].copy_from_slice(&signature.s);
+        let sig = RecoverableSignature::from_compact(&compact, recid)
+            .map_err(|_| Eip712Error::InvalidSignature)?;
+        let msg = Message::from_slice(&self.signing_hash(message)).expect("32-byte hash");
+        let pubkey = Secp256k1::verification_only()
+            .recover_ecdsa(&msg, &sig)
+            .map_err(|_| Eip712Error::InvalidSignature)?;
+        Ok(account_from_pubkey(&pubkey))
+    }
+
+    pub fn verify(
+        &self,
+        message: &impl Eip712Struct,
+        signature: &Eip712Signature,
+        signer: &Account,
+    ) -> Result<(), Eip712Error> {
+        let found = self.recover(message, signature)?;
+        if &found != signer {
+            return Err(Eip712Error::WrongSigner {
+                expected: *signer,
+                found,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Recoverable secp256k1 signature in Ethereum `(v, r, s)` form.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Eip712Signature {
+    pub v: u8,
+    pub r: Hash,
+    pub s: Hash,
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::hex::FromHex;
+
+    use super::*;
+
+    // `Mail` example from the EIP-712 specification
+    struct Person {
+        name: &'static str,
+        wallet: Account,
+    }
+
+    impl Eip712Struct for Person {
+        const ENCODED_TYPE: &'static str = "Person(string name,address wallet)";
+
+        fn encode_data(&self) -> Vec<Hash> {
+            vec![encode_string(self.name), encode_address(&self.wallet)]
+        }
+    }
+
+    struct Mail {
+        from: Person,
+        to: Person,
+        contents: &'static str,
+    }
+
+    impl Eip712Struct for Mail {
+        const ENCODED_TYPE: &'static str =
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)";
+
+        fn encode_data(&self) -> Vec<Hash> {
+            vec![self.from.struct_hash(), self.to.struct_hash(), encode_string(self.contents)]
+        }
+    }
+
+    fn hash(hex: &str) -> Hash {
+        Vec::<u8>::from_hex(hex).unwrap().try_into().unwrap()
+    }
+
+    fn account(hex: &str) -> Account {
+        Account::from(<[u8; 20]>::try_from(Vec::<u8>::from_hex(hex).unwrap()).unwrap())
+    }
+
+    fn domain() -> Eip712Domain {
+        Eip712Domain {
+            name: Some(s!("Ether Mail")),
+            version: Some(s!("1")),
+            chain_id: Some(U256::from(1u64)),
+            verifying_contract: Some(account("cccccccccccccccccccccccccccccccccccccccc")),
+            salt: None,
+        }
+    }
+
+    fn mail() -> Mail {
+        Mail {
+            from: Person {
+                name: "Cow",
+                wallet: account("cd2a3d9f938e13cd947ec05abc7fe734df8dd826"),
+            },
+            to: Person {
+                name: "Bob",
+                wallet: account("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+            },
+            contents: "Hello, Bob!",
+        }
+    }
+
+    fn cow_key() -> SecretKey { SecretKey::from_slice(&keccak(b"cow")).unwrap() }
+
+    #[test]
+    fn domain_separator() {
+        assert_eq!(
+            domain().encoded_type(),
+            "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)"
+        );
+        assert_eq!(
+            domain().separator(),
+            hash("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+        );
+    }
+
+    #[test]
+    fn struct_hash() {
+        assert_eq!(
+            Mail::type_hash(),
+            hash("a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2")
+        );
+        assert_eq!(
+            mail().struct_hash(),
+            hash("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e")
+        );
+    }
+
+    #[test]
+    fn digest() {
+        assert_eq!(
+            domain().signing_hash(&mail()),
+            hash("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+        );
+    }
+
+    #[test]
+    fn sign_recover() {
+        let pubkey = PublicKey::from_secret_key(&Secp256k1::signing_only(), &cow_key());
+        assert_eq!(account_from_pubkey(&pubkey), mail().from.wallet);
+
+        let signature = domain().sign(&mail(), &cow_key());
+        assert_eq!(signature, Eip712Signature {
+            v: 28,
+            r: hash("4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d"),
+            s: hash("07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562"),
+        });
+        domain().verify(&mail(), &signature, &mail().from.wallet).unwrap();
+        assert_eq!(
+            domain().verify(&mail(), &signature, &mail().to.wallet),
+            Err(Eip712Error::WrongSigner {
+                expected: mail().to.wallet,
+                found: mail().from.wallet,
+            })
+        );
+    }
+}
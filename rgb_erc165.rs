@@ -0,0 +1,102 @@
+//ERC-165 interface detection
+use std::collections::BTreeSet;
+
+use rgbstd::interface::IfaceImpl;
+
+use crate::rgb_abi::{self, AbiError, Selector, Token};
+use crate::rgb_e1155_interface::rgb_e1155;
+use crate::rgb_e20_interface::rgb_e20;
+use crate::rgb_e721_interface::{rgb_e721, rgb_e721_rental, rgb_e721_royalty};
+
+pub type InterfaceId = [u8; 4];
+
+pub const ERC165: InterfaceId = [0x01, 0xff, 0xc9, 0xa7];
+pub const ERC20: InterfaceId = [0x36, 0x37, 0x2b, 0x07];
+pub const ERC20_PERMIT: InterfaceId = [0x9d, 0x8f, 0xf7, 0xda];
+pub const ERC721: InterfaceId = [0x80, 0xac, 0x58, 0xcd];
+pub const ERC721_METADATA: InterfaceId = [0x5b, 0x5e, 0x13, 0x9f];
+pub const ERC721_ENUMERABLE: InterfaceId = [0x78, 0x0e, 0x9d, 0x63];
+pub const ERC2981: InterfaceId = [0x2a, 0x55, 0x20, 0x5a];
+pub const ERC5192: InterfaceId = [0xb4, 0x5a, 0x3c, 0x0e];
+pub const ERC4907: InterfaceId = [0xad, 0x09, 0x2b, 0x5c];
+pub const ERC1155: InterfaceId = [0xd9, 0xb6, 0x7a, 0x26];
+pub const ERC1155_METADATA_URI: InterfaceId = [0x0e, 0x89, 0x34, 0x1c];
+pub const INVALID: InterfaceId = [0xff, 0xff, 0xff, 0xff];
+
+pub const SEL_SUPPORTS_INTERFACE: Selector = ERC165;
+
+fn has_global(iimpl: &IfaceImpl, name: &str) -> bool {
+    iimpl.global_state.iter().any(|field| field.name.as_str() == name)
+}
+
+fn has_assignment(iimpl: &IfaceImpl, name: &str) -> bool {
+    iimpl.assignments.iter().any(|field| field.name.as_str() == name)
+}
+
+fn has_transition(iimpl: &IfaceImpl, name: &str) -> bool {
+    iimpl.transitions.iter().any(|ty| ty.name.as_str() == name)
+}
+
+/// ERC-165 interface ids answered by the contract whose schema is bound to
+/// the given interface implementations.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct SupportedInterfaces(BTreeSet<InterfaceId>);
+
+impl SupportedInterfaces {
+    pub fn from_impls<'a>(impls: impl IntoIterator<Item = &'a IfaceImpl>) -> Self {
+        let mut ids = bset![ERC165];
+        let e20 = rgb_e20().iface_id();
+        let e721 = rgb_e721().iface_id();
+        let e721_royalty = rgb_e721_royalty().iface_id();
+        let e721_rental = rgb_e721_rental().iface_id();
+        let e1155 = rgb_e1155().iface_id();
+        for iimpl in impls {
+            if iimpl.iface_id == e20 {
+                ids.insert(ERC20);
+                if has_transition(iimpl, "permit") {
+                    ids.insert(ERC20_PERMIT);
+                }
+            } else if iimpl.iface_id == e721 {
+                ids.insert(ERC721);
+                if has_global(iimpl, "name") && has_global(iimpl, "symbol") {
+                    ids.insert(ERC721_METADATA);
+                }
+                if has_global(iimpl, "total_supply")
+                    && has_global(iimpl, "token_by_index")
+                    && has_global(iimpl, "token_of_owner_by_index")
+                {
+                    ids.insert(ERC721_ENUMERABLE);
+                }
+                if has_global(iimpl, "locked") || has_assignment(iimpl, "locked_beneficiary") {
+                    ids.insert(ERC5192);
+                }
+            } else if iimpl.iface_id == e721_royalty {
+                ids.insert(ERC2981);
+            } else if iimpl.iface_id == e721_rental {
+                ids.insert(ERC4907);
+            } else if iimpl.iface_id == e1155 {
+                ids.insert(ERC1155);
+                if has_global(iimpl, "uri") {
+                    ids.insert(ERC1155_METADATA_URI);
+                }
+            }
+        }
+        SupportedInterfaces(ids)
+    }
+
+    pub fn supports_interface(&self, interface_id: InterfaceId) -> bool {
+        interface_id != INVALID && self.0.contains(&interface_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &InterfaceId> { self.0.iter() }
+
+    /// Answers an ABI-encoded `supportsInterface(bytes4)` call.
+    pub fn answer_call(&self, data: &[u8]) -> Result<Vec<u8>, AbiError> {
+        let (selector, mut r) = rgb_abi::split_selector(data)?;
+        if selector != SEL_SUPPORTS_INTERFACE {
+            return Err(AbiError::UnknownSelector(selector));
+        }
+        let supported = self.supports_interface(r.bytes4()?);
+        Ok(rgb_abi::encode_tokens(&[Token::bool(supported)]))
+    }
+}
@@ -0,0 +1,545 @@
+//RGB-E20/E721 transfer history
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+
+use amplify::confinement::{Confined, U16};
+use bitcoin::hashes::Hash;
+use bp::{Outpoint, Txid};
+use rgbstd::contract::{ContractId, Opout};
+use rgbstd::interface::IfaceImpl;
+use rgbstd::stl::Allocation;
+use rgbstd::{Genesis, OpId, Operation, Transition};
+use serde::Serialize;
+use strict_encoding::StrictDeserialize;
+
+use crate::rgb_e20_interface::Amount256;
+use crate::rgb_resolver::{Resolver, ResolverError};
+
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum HistoryError {
+    /// witness resolver error: {0}.
+    #[from]
+    Resolver(ResolverError),
+    /// I/O error: {0}.
+    #[from]
+    Io(io::Error),
+    /// JSON error: {0}.
+    #[from]
+    Json(serde_json::Error),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Display, Serialize)]
+#[display(lowercase)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    Transfer,
+    Mint,
+    Burn,
+    Approval,
+    Other,
+}
+
+impl EntryKind {
+    /// Classifies a transition by its name, which interface implementations
+    /// spell in snake case (`transfer_from`) or camel case (`safeTransferFrom`).
+    fn classify(name: &str) -> Self {
+        match name.replace('_', "").to_lowercase().as_str() {
+            "transfer" | "batchtransfer" | "transferfrom" | "safetransferfrom"
+            | "safebatchtransferfrom" => EntryKind::Transfer,
+            "mint" => EntryKind::Mint,
+            "burn" => EntryKind::Burn,
+            "approve" | "permit" | "setapprovalforall" | "setapproveforall" => {
+                EntryKind::Approval
+            }
+            _ => EntryKind::Other,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Display, Serialize)]
+#[display(lowercase)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Incoming,
+    Outgoing,
+}
+
+/// State moved by a ledger entry.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Value {
+    Amount(#[serde(serialize_with = "amount_string")] Amount256),
+    Token(u32),
+    None,
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Amount(amount) => write!(f, "{}", amount.value()),
+            Value::Token(index) => write!(f, "token {index}"),
+            Value::None => f.write_str("-"),
+        }
+    }
+}
+
+fn amount_string<S: serde::Serializer>(amount: &Amount256, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_str(&amount.value())
+}
+
+impl Value {
+    fn decode(data: &[u8]) -> Self {
+        if let Ok(amount) = <[u8; 32]>::try_from(data) {
+            return Value::Amount(Amount256::from(amount));
+        }
+        Confined::try_from(data.to_vec())
+            .ok()
+            .and_then(|data| Allocation::from_strict_serialized::<U16>(data).ok())
+            .map(|allocation| Value::Token(allocation.token_index().into()))
+            .unwrap_or(Value::None)
+    }
+}
+
+/// A single movement of contract state to or from an account (seal).
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct Entry {
+    pub contract_id: ContractId,
+    pub opid: OpId,
+    pub operation: String,
+    pub kind: EntryKind,
+    pub account: Outpoint,
+    pub direction: Direction,
+    pub value: Value,
+    /// `None` for genesis.
+    pub witness: Option<Txid>,
+    /// `None` for genesis and tentative (not yet mined) witnesses.
+    pub height: Option<u32>,
+    pub timestamp: Option<i64>,
+}
+
+/// Filter for [`Ledger::query`]; empty fields match everything. Time bounds
+/// are inclusive UNIX timestamps; entries without a timestamp never match
+/// them.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Query {
+    pub contract_id: Option<ContractId>,
+    pub account: Option<Outpoint>,
+    pub kind: Option<EntryKind>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl Query {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        let in_range = |time: i64| {
+            self.from.map_or(true, |from| time >= from) && self.to.map_or(true, |to| time <= to)
+        };
+        let timed = (self.from.is_none() && self.to.is_none())
+            || entry.timestamp.is_some_and(in_range);
+        self.contract_id.map_or(true, |id| id == entry.contract_id)
+            && self.account.map_or(true, |account| account == entry.account)
+            && self.kind.map_or(true, |kind| kind == entry.kind)
+            && timed
+    }
+}
+
+/// Witness of an operation as seen by the resolver.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct Witnessed {
+    txid: Txid,
+    height: Option<u32>,
+    timestamp: Option<i64>,
+}
+
+/// Orders transitions by witness height, tentative ones last, moving each
+/// after the transitions it spends from. Ties are broken by witness txid and
+/// opid.
+fn ordered(mut pending: Vec<(Witnessed, Transition)>) -> Vec<(Witnessed, Transition)> {
+    pending.sort_by_key(|(witness, transition)| {
+        (witness.height.is_none(), witness.height, witness.txid, transition.id())
+    });
+    let mut left = pending
+        .iter()
+        .map(|(_, transition)| transition.id())
+        .collect::<BTreeSet<_>>();
+    let mut ordered = Vec::with_capacity(pending.len());
+    while !pending.is_empty() {
+        // valid transitions can't spend from each other in a cycle, but fall
+        // back to the first one left rather than loop forever
+        let pos = pending
+            .iter()
+            .position(|(_, transition)| {
+                transition.inputs.iter().all(|input| !left.contains(&input.prev_out.op))
+            })
+            .unwrap_or(0);
+        let (witness, transition) = pending.remove(pos);
+        left.remove(&transition.id());
+        ordered.push((witness, transition));
+    }
+    ordered
+}
+
+/// Ledger of validated contract operations ordered by witness height, with
+/// tentative ones last.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Ledger {
+    entries: Vec<Entry>,
+    // owners and values of assignments, to resolve what inputs spend
+    outputs: BTreeMap<Opout, (Outpoint, Value)>,
+    indexed: BTreeSet<OpId>,
+}
+
+impl Ledger {
+    pub fn new() -> Self { default!() }
+
+    pub fn entries(&self) -> &[Entry] { &self.entries }
+
+    /// Indexes a contract from its genesis and validated transitions, taking
+    /// witness heights and times from `resolver`. Transitions may come in
+    /// any order; transitions whose witness is not mined yet are indexed
+    /// as tentative, without height and timestamp. Operations indexed by a
+    /// previous call are skipped, so a contract can be re-indexed as new
+    /// transitions arrive; witnesses still tentative are resolved again and
+    /// their entries move into place once mined.
+    pub fn index(
+        &mut self,
+        iimpl: &IfaceImpl,
+        genesis: &Genesis,
+        transitions: impl IntoIterator<Item = (Transition, Txid)>,
+        resolver: &impl Resolver,
+    ) -> Result<(), HistoryError> {
+        self.update_tentative(resolver)?;
+
+        let contract_id = genesis.contract_id();
+        if self.indexed.insert(genesis.id()) {
+            self.add_operation(contract_id, "Genesis", EntryKind::Mint, genesis, None);
+        }
+
+        let mut pending = vec![];
+        for (transition, txid) in transitions {
+            if self.indexed.contains(&transition.id()) {
+                continue;
+            }
+            let witness_txid = bitcoin::Txid::from_byte_array(txid.to_byte_array());
+            let resolved = resolver.resolve_witness(witness_txid)?;
+            let witness = Witnessed {
+                txid,
+                height: resolved.height,
+                timestamp: resolved.height.and(resolved.timestamp),
+            };
+            pending.push((witness, transition));
+        }
+
+        for (witness, transition) in ordered(pending) {
+            if !self.indexed.insert(transition.id()) {
+                continue;
+            }
+            let name = iimpl
+                .transitions
+                .iter()
+                .find(|named| named.id == transition.transition_type)
+                .map(|named| named.name.to_string())
+                .unwrap_or_else(|| transition.transition_type.to_string());
+            let kind = EntryKind::classify(&name);
+            for input in transition.inputs.iter() {
+                if let Some((account, value)) = self.outputs.get(&input.prev_out).copied() {
+                    self.entries.push(Entry {
+                        contract_id,
+                        opid: transition.id(),
+                        operation: name.clone(),
+                        kind,
+                        account,
+                        direction: Direction::Outgoing,
+                        value,
+                        witness: Some(witness.txid),
+                        height: witness.height,
+                        timestamp: witness.timestamp,
+                    });
+                }
+            }
+            self.add_operation(contract_id, &name, kind, &transition, Some(witness));
+        }
+
+        // entries of earlier calls may be mined above the ones just added;
+        // the sort is stable, so spent entries stay ahead of their spenders
+        self.entries
+            .sort_by_key(|entry| (entry.witness.is_some(), entry.height.is_none(), entry.height));
+        Ok(())
+    }
+
+    /// Sets the height and timestamp of entries whose witness was tentative
+    /// and is mined now. Witnesses the resolver no longer knows stay
+    /// tentative.
+    fn update_tentative(&mut self, resolver: &impl Resolver) -> Result<(), HistoryError> {
+        let tentative = self
+            .entries
+            .iter()
+            .filter(|entry| entry.height.is_none())
+            .filter_map(|entry| entry.witness)
+            .collect::<BTreeSet<_>>();
+        for txid in tentative {
+            let witness_txid = bitcoin::Txid::from_byte_array(txid.to_byte_array());
+            let resolved = match resolver.resolve_witness(witness_txid) {
+                Ok(resolved) => resolved,
+                Err(ResolverError::Unknown(_)) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let Some(height) = resolved.height else {
+                continue;
+            };
+            for entry in self.entries.iter_mut().filter(|entry| entry.witness == Some(txid)) {
+                entry.height = Some(height);
+                entry.timestamp = resolved.timestamp;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_operation(
+        &mut self,
+        contract_id: ContractId,
+        name: &str,
+        kind: EntryKind,
+        op: &impl Operation,
+        witness: Option<Witnessed>,
+    ) {
+        let opid = op.id();
+        // genesis seals always point to existing transactions
+        let txid = witness.map_or_else(Txid::all_zeros, |witness| witness.txid);
+        for (ty, assigns) in op.assignments().iter() {
+            for no in 0..assigns.len_u16() {
+                let Ok(Some(seal)) = assigns.revealed_seal_at(no) else {
+                    continue;
+                };
+                let account = seal.outpoint_or(txid);
+                let value = assigns
+                    .as_structured()
+                    .get(no as usize)
+                    .and_then(|assign| assign.as_revealed_state())
+                    .map(|data| Value::decode(data.as_ref()))
+                    .unwrap_or(Value::None);
+                self.outputs.insert(Opout::new(opid, *ty, no), (account, value));
+                self.entries.push(Entry {
+                    contract_id,
+                    opid,
+                    operation: name.to_owned(),
+                    kind,
+                    account,
+                    direction: Direction::Incoming,
+                    value,
+                    witness: witness.map(|witness| witness.txid),
+                    height: witness.and_then(|witness| witness.height),
+                    timestamp: witness.and_then(|witness| witness.timestamp),
+                });
+            }
+        }
+    }
+
+    pub fn query<'a>(&'a self, query: &'a Query) -> impl Iterator<Item = &'a Entry> + 'a {
+        self.entries.iter().filter(move |entry| query.matches(entry))
+    }
+
+    pub fn export_json<'a>(
+        entries: impl IntoIterator<Item = &'a Entry>,
+        writer: impl Write,
+    ) -> Result<(), HistoryError> {
+        let entries = entries.into_iter().collect::<Vec<_>>();
+        serde_json::to_writer_pretty(writer, &entries)?;
+        Ok(())
+    }
+
+    pub fn export_csv<'a>(
+        entries: impl IntoIterator<Item = &'a Entry>,
+        mut writer: impl Write,
+    ) -> Result<(), HistoryError> {
+        writeln!(
+            writer,
+            "contract_id,opid,operation,kind,account,direction,value,witness,height,timestamp"
+        )?;
+        for entry in entries {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{}",
+                entry.contract_id,
+                entry.opid,
+                entry.operation,
+                entry.kind,
+                entry.account,
+                entry.direction,
+                entry.value,
+                entry.witness.map(|txid| txid.to_string()).unwrap_or_default(),
+                entry.height.map(|height| height.to_string()).unwrap_or_default(),
+                entry.timestamp.map(|time| time.to_string()).unwrap_or_default(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::SmallBlob;
+    use amplify::Wrapper;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::{ScriptBuf, Sequence, Transaction, TxIn, TxOut};
+    use bp::Chain;
+    use rgbstd::contract::{GenesisSeal, GraphSeal};
+    use rgbstd::schema::SchemaId;
+    use rgbstd::{Assign, Assignments, Input, Inputs, RevealedData, TypedAssigns};
+
+    use super::*;
+    use crate::rgb_e20_schema::{nia_rgb_e20, OS_ASSETS};
+    use crate::rgb_resolver::FileResolver;
+
+    fn seal(n: u8) -> Outpoint { Outpoint::new([n; 32].into(), 0) }
+
+    fn amount(value: u64) -> RevealedData {
+        RevealedData::from(SmallBlob::try_from(Amount256::from(value).to_vec()).unwrap())
+    }
+
+    fn tx(n: u8) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: bitcoin::OutPoint::new(bitcoin::Txid::from_byte_array([n; 32]), 0),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: bitcoin::Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 1_000,
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    fn txid(n: u8) -> Txid { tx(n).txid().to_byte_array().into() }
+
+    /// Genesis issuing 10 tokens to seal 1 and 5 to seal 2.
+    fn genesis() -> Genesis {
+        Genesis {
+            ffv: zero!(),
+            schema_id: SchemaId::from([0x5c; 32]),
+            chain: Chain::Regtest,
+            metadata: none!(),
+            globals: none!(),
+            assignments: Assignments::from_inner(confined_bmap! {
+                OS_ASSETS => TypedAssigns::Structured(confined_vec![
+                    Assign::revealed(GenesisSeal::from(seal(1)), amount(10)),
+                    Assign::revealed(GenesisSeal::from(seal(2)), amount(5)),
+                ]),
+            }),
+            valencies: none!(),
+        }
+    }
+
+    /// Transfer of the `no`th genesis allocation to seal `to`.
+    fn transfer(genesis: &Genesis, no: u16, to: u8, value: u64) -> Transition {
+        let input = Input::with(Opout::new(genesis.id(), OS_ASSETS, no));
+        Transition {
+            ffv: zero!(),
+            contract_id: genesis.contract_id(),
+            transition_type: 20000,
+            metadata: none!(),
+            globals: none!(),
+            inputs: Inputs::from_inner(confined_bset! { input }),
+            assignments: Assignments::from_inner(confined_bmap! {
+                OS_ASSETS => TypedAssigns::Structured(confined_vec![
+                    Assign::revealed(GraphSeal::from(seal(to)), amount(value)),
+                ]),
+            }),
+            valencies: none!(),
+        }
+    }
+
+    #[test]
+    fn chain() {
+        let iimpl = nia_rgb_e20();
+        let genesis = genesis();
+        let first = transfer(&genesis, 0, 3, 10);
+        let second = transfer(&genesis, 1, 4, 5);
+        let mut resolver = FileResolver::default();
+        resolver.add(tx(1), None, None);
+
+        let mut ledger = Ledger::new();
+        ledger.index(&iimpl, &genesis, [(first.clone(), txid(1))], &resolver).unwrap();
+        let summary = |ledger: &Ledger| {
+            ledger
+                .entries()
+                .iter()
+                .map(|entry| (entry.account, entry.direction, entry.value, entry.height))
+                .collect::<Vec<_>>()
+        };
+        let value = |amount: u64| Value::Amount(Amount256::from(amount));
+        assert_eq!(summary(&ledger), vec![
+            (seal(1), Direction::Incoming, value(10), None),
+            (seal(2), Direction::Incoming, value(5), None),
+            (seal(1), Direction::Outgoing, value(10), None),
+            (seal(3), Direction::Incoming, value(10), None),
+        ]);
+
+        // the first transfer is mined after the second one, which arrives
+        // later; already indexed operations are passed again
+        resolver.add(tx(1), Some(101), Some(1_700_000_600));
+        resolver.add(tx(2), Some(100), Some(1_700_000_000));
+        let transitions = [(second.clone(), txid(2)), (first, txid(1))];
+        ledger.index(&iimpl, &genesis, transitions, &resolver).unwrap();
+        assert_eq!(summary(&ledger), vec![
+            (seal(1), Direction::Incoming, value(10), None),
+            (seal(2), Direction::Incoming, value(5), None),
+            (seal(2), Direction::Outgoing, value(5), Some(100)),
+            (seal(4), Direction::Incoming, value(5), Some(100)),
+            (seal(1), Direction::Outgoing, value(10), Some(101)),
+            (seal(3), Direction::Incoming, value(10), Some(101)),
+        ]);
+        let entries = ledger.entries();
+        assert_eq!(entries[0].kind, EntryKind::Mint);
+        assert_eq!(entries[2].kind, EntryKind::Transfer);
+        assert_eq!(entries[5].timestamp, Some(1_700_000_600));
+
+        let query = Query {
+            account: Some(seal(1)),
+            from: Some(1_700_000_000),
+            ..default!()
+        };
+        assert_eq!(ledger.query(&query).collect::<Vec<_>>(), vec![&entries[4]]);
+
+        let mut csv = vec![];
+        Ledger::export_csv(&entries[..3], &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "contract_id,opid,operation,kind,account,direction,value,witness,height,timestamp"
+        );
+        let contract_id = genesis.contract_id();
+        let opid = genesis.id();
+        let seal1 = seal(1);
+        assert_eq!(lines[1], format!("{contract_id},{opid},Genesis,mint,{seal1},incoming,10,,,"));
+        assert_eq!(
+            lines[3],
+            format!(
+                "{contract_id},{},transfer,transfer,{},outgoing,5,{},100,1700000000",
+                second.id(),
+                seal(2),
+                txid(2)
+            )
+        );
+
+        let mut json = vec![];
+        Ledger::export_json(entries, &mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        let json = json.as_array().unwrap();
+        assert_eq!(json.len(), 6);
+        assert_eq!(json[0]["kind"], "mint");
+        assert_eq!(json[0]["witness"], serde_json::Value::Null);
+        assert_eq!(json[2]["direction"], "outgoing");
+        assert_eq!(json[2]["value"]["amount"], "5");
+        assert_eq!(json[2]["height"], 100);
+        assert_eq!(json[2]["witness"], txid(2).to_string());
+    }
+}
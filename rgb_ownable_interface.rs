@@ -0,0 +1,114 @@
+//RGB-Ownable
+use bp::bc::stl::bitcoin_stl;
+use strict_types::stl::std_stl;
+use strict_types::{CompileError, LibBuilder, TypeLib};
+
+use super::{AssignIface, GenesisIface, Iface, OwnedIface, Req, TransitionIface, VerNo};
+use crate::interface::ArgSpec;
+use crate::stl::{rgb_contract_stl, StandardTypes};
+
+pub const LIB_NAME_RGB_OWNABLE: &str = "RGB-Ownable";
+
+const NOT_OWNER: u8 = 1;
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_OWNABLE, tags = repr, into_u8, try_from_u8)]
+#[repr(u8)]
+pub enum Error {
+    #[strict_type(dumb)]
+    NotOwner = NOT_OWNER,
+}
+
+/// Owner right: the seal holding it authorizes owner-only operations, the
+/// account is what `owner()` reports.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_OWNABLE)]
+pub struct OwnerRight {
+    pub owner: Account,
+}
+
+fn _rgb_ownable_stl() -> Result<TypeLib, CompileError> {
+    LibBuilder::new(libname!(LIB_NAME_RGB_OWNABLE), tiny_bset! {
+        std_stl().to_dependency(),
+        bitcoin_stl().to_dependency(),
+        rgb_contract_stl().to_dependency()
+    })
+    .transpile::<OwnerRight>()
+    .transpile::<Error>()
+    .compile()
+}
+
+pub fn rgb_ownable_stl() -> TypeLib {
+    _rgb_ownable_stl().expect("invalid strict type RGB-Ownable library")
+}
+
+pub fn rgb_ownable() -> Iface {
+    let types = StandardTypes::with(rgb_ownable_stl());
+
+    Iface {
+        version: VerNo::V1,
+        name: tn!("RGB-Ownable"),
+        global_state: none!(),
+        assignments: tiny_bmap! {
+            fname!("owner") => AssignIface::public(OwnedIface::Data(types.get("RGB-Ownable.OwnerRight")), Req::Optional),
+        },
+        genesis: GenesisIface {
+            metadata: None,
+            global: none!(),
+            assignments: tiny_bmap! {
+                fname!("owner") => ArgSpec::required(),
+            },
+            valencies: none!(),
+            errors: none!(),
+        },
+        transitions: tiny_bmap! {
+            tn!("TransferOwnership") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("owner"),
+                    fname!("new_owner") => ArgSpec::from_required("Account"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    NOT_OWNER
+                },
+                default_assignment: Some(fname!("owner")),
+            },
+            tn!("RenounceOwnership") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    fname!("used") => ArgSpec::from_required("owner"),
+                },
+                valencies: none!(),
+                errors: tiny_bset! {
+                    NOT_OWNER
+                },
+                default_assignment: None,
+            },
+            tn!("Owner") => TransitionIface {
+                optional: false,
+                metadata: None,
+                globals: none!(),
+                inputs: none!(),
+                valencies: none!(),
+                errors: none!(),
+                default_assignment: None,
+            },
+        },
+        extensions: none!(),
+        error_type: types.get("RGB-Ownable.Error"),
+        default_operation: None,
+    }
+}
+
+pub trait RgbOwnableIface {
+    fn owner() -> Option<Account>;
+    fn transfer_ownership(new_owner: Account);
+    fn renounce_ownership();
+}
@@ -0,0 +1,74 @@
+///RGB-Ownable SCHEMA
+use aluvm::library::Lib;
+use rgbstd::interface::rgb_ownable::Error;
+use rgbstd::interface::{rgb_ownable, IfaceImpl, NamedField, NamedType, VerNo};
+use rgbstd::schema::{Occurrences, SchemaId, TransitionSchema};
+use strict_types::{SemId, Ty};
+
+use crate::rgb_alu::{all_of, any_inputs};
+
+pub const OS_OWNER_RIGHT: u16 = 30010;
+pub const TS_TRANSFER_OWNERSHIP: u16 = 30010;
+pub const TS_RENOUNCE_OWNERSHIP: u16 = 30011;
+pub const TS_OWNER: u16 = 30012;
+
+pub fn transfer_ownership_schema() -> TransitionSchema {
+    TransitionSchema {
+        metadata: Ty::<SemId>::UNIT.id(None),
+        globals: none!(),
+        inputs: tiny_bmap! {
+            OS_OWNER_RIGHT => Occurrences::Once
+        },
+        assignments: tiny_bmap! {
+            OS_OWNER_RIGHT => Occurrences::Once
+        },
+        valencies: none!(),
+    }
+}
+
+/// Transferring or renouncing ownership is authorized by spending the owner
+/// right.
+pub fn ownership_lib() -> Lib { all_of([any_inputs(&[OS_OWNER_RIGHT], Error::NotOwner as u8)]) }
+
+pub fn renounce_ownership_schema() -> TransitionSchema {
+    TransitionSchema {
+        metadata: Ty::<SemId>::UNIT.id(None),
+        globals: none!(),
+        inputs: tiny_bmap! {
+            OS_OWNER_RIGHT => Occurrences::Once
+        },
+        assignments: none!(),
+        valencies: none!(),
+    }
+}
+
+pub fn owner_schema() -> TransitionSchema {
+    TransitionSchema {
+        metadata: Ty::<SemId>::UNIT.id(None),
+        globals: none!(),
+        inputs: none!(),
+        assignments: none!(),
+        valencies: none!(),
+    }
+}
+
+pub fn ownable_impl(schema_id: SchemaId) -> IfaceImpl {
+    let iface = rgb_ownable();
+
+    IfaceImpl {
+        version: VerNo::V1,
+        schema_id,
+        iface_id: iface.iface_id(),
+        global_state: none!(),
+        assignments: tiny_bset! {
+            NamedField::with(OS_OWNER_RIGHT, fname!("owner")),
+        },
+        valencies: none!(),
+        transitions: tiny_bset! {
+            NamedType::with(TS_TRANSFER_OWNERSHIP, tn!("transfer_ownership")),
+            NamedType::with(TS_RENOUNCE_OWNERSHIP, tn!("renounce_ownership")),
+            NamedType::with(TS_OWNER, tn!("owner")),
+        },
+        extensions: none!(),
+    }
+}
@@ -0,0 +1,390 @@
+//PSBT construction for RGB-E20/E721 transfers
+use std::collections::{BTreeMap, BTreeSet};
+
+use bitcoin::hashes::Hash;
+use bitcoin::psbt::Psbt;
+use bitcoin::{absolute, OutPoint, Sequence, Transaction, TxIn, TxOut, Witness};
+use bp::dbc::Anchor;
+use bp::seals::txout::CloseMethod;
+use bp::Outpoint;
+use commit_verify::mpc::MerkleBlock;
+use rgbstd::containers::{Bindle, Transfer};
+use rgbstd::contract::{ContractId, OpId, SecretSeal};
+use rgbstd::persistence::{Inventory, Stock};
+use rgbstd::{Transition, TransitionBundle};
+use rgbwallet::psbt::{DbcPsbtError, PsbtDbc, RgbExt, RgbInExt};
+
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum PsbtError {
+    /// outpoint {0} is not in the UTXO set.
+    UnknownUtxo(Outpoint),
+    /// transfer spends no outputs.
+    NoInputs,
+    /// commitment host output {0} does not exist.
+    NoHost(usize),
+    /// deterministic bitcoin commitment error: {0}.
+    #[from]
+    Dbc(DbcPsbtError),
+    /// stash error: {0}.
+    Stash(String),
+}
+
+/// Unspent outputs known to the wallet, kept in memory so transfers can be
+/// put together without a blockchain backend.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct UtxoSet(BTreeMap<Outpoint, TxOut>);
+
+impl UtxoSet {
+    pub fn new() -> Self { default!() }
+
+    pub fn add(&mut self, outpoint: Outpoint, txout: TxOut) -> Option<TxOut> {
+        self.0.insert(outpoint, txout)
+    }
+
+    pub fn get(&self, outpoint: Outpoint) -> Option<&TxOut> { self.0.get(&outpoint) }
+
+    /// Removes the outputs spent by `tx` and adds the ones it creates.
+    pub fn apply(&mut self, tx: &Transaction) {
+        for input in &tx.input {
+            self.0.remove(&from_bitcoin(input.previous_output));
+        }
+        let txid = tx.txid();
+        for (vout, txout) in tx.output.iter().enumerate() {
+            let outpoint = OutPoint::new(txid, vout as u32);
+            self.0.insert(from_bitcoin(outpoint), txout.clone());
+        }
+    }
+
+    pub fn total(&self) -> u64 { self.0.values().map(|txout| txout.value).sum() }
+}
+
+fn to_bitcoin(outpoint: Outpoint) -> OutPoint {
+    OutPoint::new(
+        bitcoin::Txid::from_byte_array(outpoint.txid.to_byte_array()),
+        outpoint.vout.into_u32(),
+    )
+}
+
+fn from_bitcoin(outpoint: OutPoint) -> Outpoint {
+    Outpoint::new(outpoint.txid.to_byte_array().into(), outpoint.vout)
+}
+
+/// Witness transaction committing to an RGB transition. The anchor and
+/// bundles stay out of the stock until [`TransferPsbt::finalize`] is called,
+/// so a PSBT which never gets signed or broadcast leaves the stock untouched.
+#[derive(Clone, Debug)]
+pub struct TransferPsbt {
+    pub psbt: Psbt,
+    pub contract_id: ContractId,
+    anchor: Anchor<MerkleBlock>,
+    bundles: BTreeMap<ContractId, TransitionBundle>,
+}
+
+impl TransferPsbt {
+    /// Registers the anchor and bundles in the stock and extracts the
+    /// consignment for `beneficiaries`. Call it once the witness transaction
+    /// is signed and broadcast.
+    pub fn finalize(
+        self,
+        stock: &mut Stock,
+        beneficiaries: impl IntoIterator<Item = SecretSeal>,
+    ) -> Result<(Psbt, Bindle<Transfer>), PsbtError> {
+        let witness_txid = self.psbt.unsigned_tx.txid();
+        stock
+            .consume_anchor(self.anchor)
+            .map_err(|e| PsbtError::Stash(e.to_string()))?;
+        for (id, bundle) in self.bundles {
+            stock
+                .consume_bundle(id, bundle, witness_txid.to_byte_array().into())
+                .map_err(|e| PsbtError::Stash(e.to_string()))?;
+        }
+        let consignment = stock
+            .transfer(self.contract_id, beneficiaries)
+            .map_err(|e| PsbtError::Stash(e.to_string()))?;
+        Ok((self.psbt, consignment))
+    }
+}
+
+/// Builds the witness PSBT of a transition produced by the `nia_schema` or
+/// `uda_schema` builders and embeds the RGB commitment into it.
+#[derive(Clone, Debug)]
+pub struct PsbtBuilder {
+    method: CloseMethod,
+    inputs: BTreeSet<Outpoint>,
+    funding: BTreeSet<Outpoint>,
+    outputs: Vec<TxOut>,
+    host: usize,
+}
+
+impl PsbtBuilder {
+    pub fn with(method: CloseMethod) -> Self {
+        PsbtBuilder {
+            method,
+            inputs: none!(),
+            funding: none!(),
+            outputs: vec![],
+            host: 0,
+        }
+    }
+
+    /// Spends a bitcoin output holding seals closed by the transition. The
+    /// input is registered as a consumer of the transition.
+    pub fn spend(mut self, outpoint: Outpoint) -> Self {
+        self.inputs.insert(outpoint);
+        self
+    }
+
+    pub fn spend_all(mut self, outpoints: impl IntoIterator<Item = Outpoint>) -> Self {
+        self.inputs.extend(outpoints);
+        self
+    }
+
+    /// Spends a bitcoin output which only pays for fees. The input carries
+    /// no RGB state and is not registered as a consumer. An output also given
+    /// to [`Self::spend`] is spent once, as a seal input.
+    pub fn fund(mut self, outpoint: Outpoint) -> Self {
+        self.funding.insert(outpoint);
+        self
+    }
+
+    pub fn output(mut self, txout: TxOut) -> Self {
+        self.outputs.push(txout);
+        self
+    }
+
+    /// Output carrying the commitment: the tapret key for
+    /// [`CloseMethod::TapretFirst`], or an `OP_RETURN` for
+    /// [`CloseMethod::OpretFirst`]. Defaults to the first output.
+    pub fn commit_to(mut self, vout: usize) -> Self {
+        self.host = vout;
+        self
+    }
+
+    /// Unsigned PSBT spending the seal inputs followed by the funding ones
+    /// from `utxos`, with the host output marked for the commitment.
+    pub fn psbt(&self, utxos: &UtxoSet) -> Result<Psbt, PsbtError> {
+        if self.inputs.is_empty() {
+            return Err(PsbtError::NoInputs);
+        }
+        let funding = self.funding.difference(&self.inputs);
+        let inputs = self.inputs.iter().chain(funding).collect::<Vec<_>>();
+        if self.host >= self.outputs.len() {
+            return Err(PsbtError::NoHost(self.host));
+        }
+        let tx = Transaction {
+            version: 2,
+            lock_time: absolute::LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|outpoint| TxIn {
+                    previous_output: to_bitcoin(**outpoint),
+                    script_sig: none!(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: self.outputs.clone(),
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).expect("unsigned transaction");
+        for (input, outpoint) in psbt.inputs.iter_mut().zip(inputs) {
+            let utxo = utxos.get(*outpoint).ok_or(PsbtError::UnknownUtxo(*outpoint))?;
+            input.witness_utxo = Some(utxo.clone());
+        }
+        let host = &mut psbt.outputs[self.host];
+        match self.method {
+            CloseMethod::TapretFirst => host.set_tapret_host().expect("fresh output"),
+            CloseMethod::OpretFirst => host.set_opret_host().expect("fresh output"),
+        }
+        Ok(psbt)
+    }
+
+    /// Registers the seal inputs of `psbt` as consumers of `opid`, leaving
+    /// the funding inputs alone.
+    fn set_consumers(
+        &self,
+        psbt: &mut Psbt,
+        contract_id: ContractId,
+        opid: OpId,
+    ) -> Result<(), PsbtError> {
+        let txins = &psbt.unsigned_tx.input;
+        for (input, txin) in psbt.inputs.iter_mut().zip(txins) {
+            if !self.inputs.contains(&from_bitcoin(txin.previous_output)) {
+                continue;
+            }
+            input
+                .set_rgb_consumer(contract_id, opid)
+                .map_err(|e| PsbtError::Stash(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Embeds `transition` into the PSBT. The stock is not touched until the
+    /// returned [`TransferPsbt`] is finalized.
+    pub fn compose(
+        &self,
+        utxos: &UtxoSet,
+        contract_id: ContractId,
+        transition: Transition,
+    ) -> Result<TransferPsbt, PsbtError> {
+        let mut psbt = self.psbt(utxos)?;
+        self.set_consumers(&mut psbt, contract_id, transition.id())?;
+
+        psbt.push_rgb_transition(transition)
+            .map_err(|e| PsbtError::Stash(e.to_string()))?;
+        let bundles = psbt.rgb_bundles().map_err(|e| PsbtError::Stash(e.to_string()))?;
+        psbt.rgb_bundle_to_lnpbp4()
+            .map_err(|e| PsbtError::Stash(e.to_string()))?;
+        let anchor = psbt.dbc_conclude(self.method)?;
+        Ok(TransferPsbt {
+            psbt,
+            contract_id,
+            anchor,
+            bundles,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::ScriptBuf;
+    use bp::dbc::Proof;
+
+    use super::*;
+
+    fn outpoint(n: u8) -> Outpoint { Outpoint::new([n; 32].into(), 0) }
+
+    fn txout(value: u64) -> TxOut {
+        TxOut {
+            value,
+            script_pubkey: ScriptBuf::new(),
+        }
+    }
+
+    fn utxos() -> UtxoSet {
+        let mut utxos = UtxoSet::new();
+        utxos.add(outpoint(1), txout(1_000));
+        utxos.add(outpoint(2), txout(1_000));
+        utxos.add(outpoint(3), txout(50_000));
+        utxos
+    }
+
+    #[test]
+    fn funding_inputs() {
+        let builder = PsbtBuilder::with(CloseMethod::OpretFirst)
+            .spend_all([outpoint(1), outpoint(2)])
+            .fund(outpoint(3))
+            .output(txout(600))
+            .output(txout(40_000));
+        let mut psbt = builder.psbt(&utxos()).unwrap();
+        let spent = psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .map(|txin| from_bitcoin(txin.previous_output))
+            .collect::<Vec<_>>();
+        assert_eq!(spent, vec![outpoint(1), outpoint(2), outpoint(3)]);
+        assert!(psbt.inputs.iter().all(|input| input.witness_utxo.is_some()));
+
+        let contract_id = ContractId::from([0xc0; 32]);
+        let opid = OpId::from([0x0b; 32]);
+        builder.set_consumers(&mut psbt, contract_id, opid).unwrap();
+        for input in &psbt.inputs[..2] {
+            assert_eq!(input.rgb_consumers().unwrap(), bmap! { contract_id => opid });
+        }
+        assert!(psbt.inputs[2].rgb_consumers().unwrap().is_empty());
+    }
+
+    #[test]
+    fn spent_once() {
+        let psbt = PsbtBuilder::with(CloseMethod::OpretFirst)
+            .spend(outpoint(1))
+            .fund(outpoint(1))
+            .fund(outpoint(3))
+            .output(txout(600))
+            .psbt(&utxos())
+            .unwrap();
+        let spent = psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .map(|txin| from_bitcoin(txin.previous_output))
+            .collect::<Vec<_>>();
+        assert_eq!(spent, vec![outpoint(1), outpoint(3)]);
+    }
+
+    #[test]
+    fn opret_compose() {
+        let contract_id = ContractId::from([0xc0; 32]);
+        let transition = Transition {
+            ffv: zero!(),
+            contract_id,
+            transition_type: 20000,
+            metadata: none!(),
+            globals: none!(),
+            inputs: none!(),
+            assignments: none!(),
+            valencies: none!(),
+        };
+        let opid = transition.id();
+        let host = TxOut {
+            value: 0,
+            script_pubkey: ScriptBuf::new_op_return(&[]),
+        };
+        let composed = PsbtBuilder::with(CloseMethod::OpretFirst)
+            .spend(outpoint(1))
+            .fund(outpoint(3))
+            .output(txout(40_000))
+            .output(host)
+            .commit_to(1)
+            .compose(&utxos(), contract_id, transition)
+            .unwrap();
+
+        let tx = &composed.psbt.unsigned_tx;
+        assert_eq!(composed.anchor.txid.to_byte_array(), tx.txid().to_byte_array());
+        assert!(matches!(composed.anchor.dbc_proof, Proof::OpretFirst));
+        assert_eq!(composed.bundles.keys().collect::<Vec<_>>(), vec![&contract_id]);
+        // the host output commits to the 32-byte MPC root, the other is left alone
+        let script = &tx.output[1].script_pubkey;
+        assert!(script.is_op_return());
+        assert_eq!(script.len(), 34);
+        assert_eq!(tx.output[0], txout(40_000));
+        assert_eq!(composed.psbt.inputs[0].rgb_consumers().unwrap(), bmap! { contract_id => opid });
+        assert!(composed.psbt.inputs[1].rgb_consumers().unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid() {
+        let builder = PsbtBuilder::with(CloseMethod::TapretFirst).output(txout(600));
+        assert!(matches!(builder.psbt(&utxos()), Err(PsbtError::NoInputs)));
+
+        let builder = builder.fund(outpoint(3));
+        assert!(matches!(builder.psbt(&utxos()), Err(PsbtError::NoInputs)));
+
+        let builder = builder.spend(outpoint(4));
+        let err = builder.psbt(&utxos()).unwrap_err();
+        assert!(matches!(err, PsbtError::UnknownUtxo(o) if o == outpoint(4)));
+
+        let builder = builder.spend(outpoint(1)).commit_to(1);
+        assert!(matches!(builder.psbt(&utxos()), Err(PsbtError::NoHost(1))));
+    }
+
+    #[test]
+    fn utxo_set() {
+        let mut utxos = utxos();
+        let psbt = PsbtBuilder::with(CloseMethod::OpretFirst)
+            .spend(outpoint(1))
+            .fund(outpoint(3))
+            .output(txout(600))
+            .output(txout(49_000))
+            .psbt(&utxos)
+            .unwrap();
+        utxos.apply(&psbt.unsigned_tx);
+        let txid = psbt.unsigned_tx.txid();
+        assert_eq!(utxos.get(outpoint(1)), None);
+        assert_eq!(utxos.get(outpoint(3)), None);
+        assert_eq!(utxos.get(outpoint(2)), Some(&txout(1_000)));
+        assert_eq!(utxos.get(from_bitcoin(OutPoint::new(txid, 1))), Some(&txout(49_000)));
+        assert_eq!(utxos.total(), 1_000 + 600 + 49_000);
+    }
+}
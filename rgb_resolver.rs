@@ -0,0 +1,299 @@
+//Witness transaction resolvers
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use amplify::hex::{FromHex, ToHex};
+use bitcoin::consensus::{deserialize, serialize};
+use bitcoin::hashes::Hash;
+use bitcoin::{Transaction, Txid};
+use electrum_client::{Client, ElectrumApi};
+use rgbstd::containers::{Bindle, Transfer};
+use rgbstd::interface::Iface;
+use rgbstd::resolvers::ResolveHeight;
+use rgbstd::validation::{Failure, ResolveTx, Status, TxResolverError};
+use serde::{Deserialize, Serialize};
+
+use crate::rgb_e20_interface::rgb_e20;
+use crate::rgb_e721_interface::rgb_e721;
+
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ResolverError {
+    /// transaction {0} is not known to the resolver.
+    Unknown(Txid),
+    /// transaction {0} is not valid hex in the resolver file.
+    Hex(Txid),
+    /// I/O error: {0}.
+    #[from]
+    Io(std::io::Error),
+    /// invalid resolver file: {0}.
+    #[from]
+    Json(serde_json::Error),
+    /// invalid transaction encoding: {0}.
+    #[from]
+    Encoding(bitcoin::consensus::encode::Error),
+    /// electrum server error: {0}.
+    #[from]
+    Electrum(electrum_client::Error),
+}
+
+/// Witness transaction with the height and time of the block mining it, if
+/// any.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Witness {
+    pub tx: Transaction,
+    pub height: Option<u32>,
+    pub timestamp: Option<i64>,
+}
+
+/// Source of witness transactions and their mining status. Validation goes
+/// through this trait only, so any blockchain backend can be plugged in.
+pub trait Resolver {
+    fn resolve_witness(&self, txid: Txid) -> Result<Witness, ResolverError>;
+
+    /// Height of the most recent block known to the resolver.
+    fn tip_height(&self) -> Result<u32, ResolverError>;
+}
+
+/// Resolver reading witnesses from a JSON file mapping txids to the raw
+/// transaction hex and the mining height and time, for offline tests.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct FileResolver {
+    witnesses: BTreeMap<Txid, Witness>,
+    tip: u32,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+struct WitnessFile {
+    tip: u32,
+    txes: BTreeMap<Txid, WitnessEntry>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+struct WitnessEntry {
+    hex: String,
+    height: Option<u32>,
+    #[serde(default)]
+    timestamp: Option<i64>,
+}
+
+impl FileResolver {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ResolverError> {
+        let file: WitnessFile = serde_json::from_slice(&fs::read(path)?)?;
+        let mut witnesses = BTreeMap::new();
+        for (txid, entry) in file.txes {
+            let bytes = Vec::<u8>::from_hex(&entry.hex).map_err(|_| ResolverError::Hex(txid))?;
+            let tx: Transaction = deserialize(&bytes)?;
+            witnesses.insert(txid, Witness {
+                tx,
+                height: entry.height,
+                timestamp: entry.timestamp,
+            });
+        }
+        Ok(FileResolver { witnesses, tip: file.tip })
+    }
+
+    pub fn store(&self, path: impl AsRef<Path>) -> Result<(), ResolverError> {
+        let file = WitnessFile {
+            tip: self.tip,
+            txes: self
+                .witnesses
+                .iter()
+                .map(|(txid, witness)| {
+                    (*txid, WitnessEntry {
+                        hex: serialize(&witness.tx).to_hex(),
+                        height: witness.height,
+                        timestamp: witness.timestamp,
+                    })
+                })
+                .collect(),
+        };
+        fs::write(path, serde_json::to_vec_pretty(&file)?)?;
+        Ok(())
+    }
+
+    pub fn add(&mut self, tx: Transaction, height: Option<u32>, timestamp: Option<i64>) {
+        if let Some(height) = height {
+            self.tip = self.tip.max(height);
+        }
+        self.witnesses.insert(tx.txid(), Witness {
+            tx,
+            height,
+            timestamp,
+        });
+    }
+
+    pub fn set_tip(&mut self, tip: u32) { self.tip = tip }
+}
+
+impl Resolver for FileResolver {
+    fn resolve_witness(&self, txid: Txid) -> Result<Witness, ResolverError> {
+        self.witnesses.get(&txid).cloned().ok_or(ResolverError::Unknown(txid))
+    }
+
+    fn tip_height(&self) -> Result<u32, ResolverError> { Ok(self.tip) }
+}
+
+/// Resolver querying an Electrum server, e.g. a local `electrs` instance.
+pub struct ElectrumResolver(Client);
+
+/// Whether an Electrum error reports a transaction unknown to the server.
+/// Servers word it differently: electrs answers "missing transaction", while
+/// ElectrumX relays "No such mempool or blockchain transaction" from bitcoind.
+pub(crate) fn is_not_found(err: &electrum_client::Error) -> bool {
+    let electrum_client::Error::Protocol(reply) = err else {
+        return false;
+    };
+    let reply = reply.to_string().to_lowercase();
+    reply.contains("missing transaction") || reply.contains("no such mempool or blockchain")
+}
+
+impl ElectrumResolver {
+    pub fn connect(url: &str) -> Result<Self, ResolverError> {
+        Ok(ElectrumResolver(Client::new(url)?))
+    }
+}
+
+impl Resolver for ElectrumResolver {
+    fn resolve_witness(&self, txid: Txid) -> Result<Witness, ResolverError> {
+        let tx = self.0.transaction_get(&txid).map_err(|err| match is_not_found(&err) {
+            true => ResolverError::Unknown(txid),
+            false => ResolverError::Electrum(err),
+        })?;
+        // Electrum reports mining heights only through script histories;
+        // heights of zero and below stand for mempool transactions.
+        let script = &tx.output.first().ok_or(ResolverError::Unknown(txid))?.script_pubkey;
+        let height = self
+            .0
+            .script_get_history(script)?
+            .into_iter()
+            .find(|entry| entry.tx_hash == txid)
+            .and_then(|entry| u32::try_from(entry.height).ok())
+            .filter(|height| *height > 0);
+        let timestamp = height
+            .map(|height| self.0.block_header(height as usize))
+            .transpose()?
+            .map(|header| header.time as i64);
+        Ok(Witness {
+            tx,
+            height,
+            timestamp,
+        })
+    }
+
+    fn tip_height(&self) -> Result<u32, ResolverError> {
+        Ok(self.0.block_headers_subscribe()?.height as u32)
+    }
+}
+
+/// Adapts a [`Resolver`] to the resolver traits of the RGB validator.
+pub struct RgbResolver<'r, R: Resolver>(pub &'r R);
+
+impl<'r, R: Resolver> ResolveTx for RgbResolver<'r, R> {
+    fn resolve_tx(&self, txid: bp::Txid) -> Result<bp::Tx, TxResolverError> {
+        let txid = Txid::from_byte_array(txid.to_byte_array());
+        let witness = self
+            .0
+            .resolve_witness(txid)
+            .map_err(|_| TxResolverError::Unknown(txid.to_byte_array().into()))?;
+        bp::Tx::consensus_deserialize(serialize(&witness.tx))
+            .map_err(|_| TxResolverError::Unknown(txid.to_byte_array().into()))
+    }
+}
+
+impl<'r, R: Resolver> ResolveHeight for RgbResolver<'r, R> {
+    type Error = ResolverError;
+
+    fn resolve_height(&mut self, txid: bp::Txid) -> Result<u32, Self::Error> {
+        let txid = Txid::from_byte_array(txid.to_byte_array());
+        Ok(self.0.resolve_witness(txid)?.height.unwrap_or_default())
+    }
+}
+
+fn validate_iface(
+    consignment: Bindle<Transfer>,
+    iface: Iface,
+    resolver: &impl Resolver,
+) -> Result<Status, Status> {
+    let consignment = consignment.unbindle();
+    if !consignment.ifaces.keys().any(|id| *id == iface.iface_id()) {
+        let mut status = Status::new();
+        status.add_failure(Failure::Custom(format!(
+            "consignment does not implement interface {}",
+            iface.name
+        )));
+        return Err(status);
+    }
+    consignment
+        .validate(&mut RgbResolver(resolver))
+        .map(|valid| valid.into_validation_status().unwrap_or_default())
+        .map_err(|invalid| invalid.into_validation_status().unwrap_or_default())
+}
+
+/// Validates an RGB-E20 transfer consignment against witnesses from `resolver`.
+pub fn validate_e20(
+    consignment: Bindle<Transfer>,
+    resolver: &impl Resolver,
+) -> Result<Status, Status> {
+    validate_iface(consignment, rgb_e20(), resolver)
+}
+
+/// Validates an RGB-E721 transfer consignment against witnesses from `resolver`.
+pub fn validate_e721(
+    consignment: Bindle<Transfer>,
+    resolver: &impl Resolver,
+) -> Result<Status, Status> {
+    validate_iface(consignment, rgb_e721(), resolver)
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::absolute::LockTime;
+    use bitcoin::{OutPoint, ScriptBuf, Sequence, TxIn, TxOut};
+
+    use super::*;
+
+    fn tx(n: u8) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::from_byte_array([n; 32]), 0),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: bitcoin::Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 1_000,
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let mut resolver = FileResolver::default();
+        resolver.add(tx(1), Some(100), Some(1_700_000_000));
+        resolver.add(tx(2), None, None);
+        assert_eq!(resolver.tip_height().unwrap(), 100);
+        resolver.set_tip(105);
+
+        let path = std::env::temp_dir().join(format!("rgb-resolver-{}.json", std::process::id()));
+        resolver.store(&path).unwrap();
+        let loaded = FileResolver::load(&path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(loaded, resolver);
+        assert_eq!(loaded.tip_height().unwrap(), 105);
+        let mined = loaded.resolve_witness(tx(1).txid()).unwrap();
+        assert_eq!(mined.tx, tx(1));
+        assert_eq!((mined.height, mined.timestamp), (Some(100), Some(1_700_000_000)));
+        let pending = loaded.resolve_witness(tx(2).txid()).unwrap();
+        assert_eq!((pending.height, pending.timestamp), (None, None));
+        assert!(matches!(
+            loaded.resolve_witness(tx(3).txid()),
+            Err(ResolverError::Unknown(txid)) if txid == tx(3).txid()
+        ));
+    }
+}
@@ -0,0 +1,681 @@
+//Persistent stash storage
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use amplify::confinement::{Confined, U32};
+use amplify::hex::{FromHex, ToHex};
+use bp::dbc::Anchor;
+use bp::{Outpoint, Txid};
+use commit_verify::mpc::MerkleBlock;
+use rgbstd::contract::{ContractId, OpId, Opout};
+use rgbstd::interface::{IfaceId, IfaceImpl};
+use rgbstd::schema::{AssignmentType, SchemaId};
+use rgbstd::{Genesis, Operation, SubSchema, Transition, TypedAssigns};
+use rusqlite::{params, Connection, OptionalExtension};
+use strict_encoding::{StrictDeserialize, StrictSerialize};
+
+use crate::rgb_e20_interface::Amount256;
+use crate::rgb_e20_schema::OS_ASSETS;
+use crate::rgb_e721_schema::{OS_ASSET, OS_LOCKED_ASSET};
+
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum StashError {
+    /// {0} {1} is not in the stash.
+    NotFound(Table, String),
+    /// stored {0} data is corrupted.
+    Corrupted(Table),
+    /// stash database has version {0}, which is newer than the supported one.
+    UnsupportedVersion(usize),
+    /// I/O error: {0}.
+    #[from]
+    Io(std::io::Error),
+    /// SQLite error: {0}.
+    #[from]
+    Sqlite(rusqlite::Error),
+}
+
+/// Kinds of records kept in a stash.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Display)]
+#[display(lowercase)]
+pub enum Table {
+    Schemas,
+    IfaceImpls,
+    Genesis,
+    Transitions,
+    Anchors,
+    Witnesses,
+    Balances,
+    TokenOwners,
+}
+
+impl Table {
+    pub const ALL: [Table; 8] = [
+        Table::Schemas,
+        Table::IfaceImpls,
+        Table::Genesis,
+        Table::Transitions,
+        Table::Anchors,
+        Table::Witnesses,
+        Table::Balances,
+        Table::TokenOwners,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Table::Schemas => "schemas",
+            Table::IfaceImpls => "iface_impls",
+            Table::Genesis => "genesis",
+            Table::Transitions => "transitions",
+            Table::Anchors => "anchors",
+            Table::Witnesses => "witnesses",
+            Table::Balances => "balances",
+            Table::TokenOwners => "token_owners",
+        }
+    }
+}
+
+fn encode(data: &impl StrictSerialize) -> Vec<u8> {
+    data.to_strict_serialized::<U32>()
+        .expect("stash records fit into 4 GB")
+        .into_inner()
+}
+
+fn decode<T: StrictDeserialize>(table: Table, bytes: Vec<u8>) -> Result<T, StashError> {
+    let bytes = Confined::try_from(bytes).map_err(|_| StashError::Corrupted(table))?;
+    T::from_strict_serialized::<U32>(bytes).map_err(|_| StashError::Corrupted(table))
+}
+
+/// Assignment types feeding the derived tables: RGB-E20 assets for
+/// [`Table::Balances`] and RGB-E721 tokens for [`Table::TokenOwners`].
+const DERIVED_TYPES: [AssignmentType; 3] = [OS_ASSETS, OS_ASSET, OS_LOCKED_ASSET];
+
+/// Keyed blob storage per [`Table`], implemented by the stash backends.
+/// Typed access lives in [`StashExt`], so the trait stays object safe and
+/// backends can be used as `dyn Stash`.
+pub trait Stash {
+    fn put(&mut self, table: Table, key: &str, value: Vec<u8>) -> Result<(), StashError>;
+    fn get(&self, table: Table, key: &str) -> Result<Option<Vec<u8>>, StashError>;
+    /// Records of `table` whose keys start with `prefix`, ordered by key.
+    fn scan(&self, table: Table, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, StashError>;
+    fn delete(&mut self, table: Table, key: &str) -> Result<(), StashError>;
+}
+
+/// Revealed structured state of type `ty` assigned by `op`, with the index
+/// and outpoint of each seal. Seals pointing to the witness transaction are
+/// resolved with `witness`, which is `None` for genesis.
+fn structured(
+    op: &impl Operation,
+    witness: Option<Txid>,
+    ty: AssignmentType,
+) -> Vec<(u16, Outpoint, Vec<u8>)> {
+    let Some(TypedAssigns::Structured(assigns)) = op.assignments_by_type(ty) else {
+        return vec![];
+    };
+    assigns
+        .iter()
+        .enumerate()
+        .filter_map(|(no, assign)| {
+            let seal = assign.revealed_seal()?;
+            let outpoint = match witness {
+                Some(txid) => seal.outpoint_or(txid),
+                None => seal.outpoint()?,
+            };
+            let data = assign.as_revealed_state()?.as_ref().to_vec();
+            Some((no as u16, outpoint, data))
+        })
+        .collect()
+}
+
+/// Typed access to a stash: contracts, their history and the state derived
+/// from it. Transitions must be put in the order they were mined, after the
+/// genesis of their contract, so the derived tables can follow them.
+pub trait StashExt: Stash {
+    fn fetch<T: StrictDeserialize>(&self, table: Table, key: String) -> Result<T, StashError> {
+        let bytes = self.get(table, &key)?.ok_or(StashError::NotFound(table, key))?;
+        decode(table, bytes)
+    }
+
+    fn put_schema(&mut self, schema: &SubSchema) -> Result<(), StashError> {
+        self.put(Table::Schemas, &schema.schema_id().to_string(), encode(schema))
+    }
+
+    fn schema(&self, schema_id: SchemaId) -> Result<SubSchema, StashError> {
+        self.fetch(Table::Schemas, schema_id.to_string())
+    }
+
+    fn put_iface_impl(&mut self, iimpl: &IfaceImpl) -> Result<(), StashError> {
+        let key = format!("{}:{}", iimpl.schema_id, iimpl.iface_id);
+        self.put(Table::IfaceImpls, &key, encode(iimpl))
+    }
+
+    fn iface_impl(&self, schema_id: SchemaId, iface_id: IfaceId) -> Result<IfaceImpl, StashError> {
+        self.fetch(Table::IfaceImpls, format!("{schema_id}:{iface_id}"))
+    }
+
+    /// Stores a genesis and derives the balances and token owners it
+    /// assigns. Putting the same genesis again changes nothing.
+    fn put_genesis(&mut self, genesis: &Genesis) -> Result<(), StashError> {
+        let contract_id = genesis.contract_id();
+        let key = contract_id.to_string();
+        if self.get(Table::Genesis, &key)?.is_some() {
+            return Ok(());
+        }
+        self.put(Table::Genesis, &key, encode(genesis))?;
+        self.derive_assigned(contract_id, genesis, None)
+    }
+
+    fn genesis(&self, contract_id: ContractId) -> Result<Genesis, StashError> {
+        self.fetch(Table::Genesis, contract_id.to_string())
+    }
+
+    /// Stores a transition mined in `witness_txid` and moves the balances and
+    /// token owners it spends to the seals it assigns. Putting the same
+    /// transition again changes nothing.
+    fn put_transition(
+        &mut self,
+        contract_id: ContractId,
+        transition: &Transition,
+        witness_txid: Txid,
+    ) -> Result<(), StashError> {
+        let key = format!("{contract_id}:{}", transition.id());
+        if self.get(Table::Transitions, &key)?.is_some() {
+            return Ok(());
+        }
+        self.put(Table::Transitions, &key, encode(transition))?;
+        self.put(Table::Witnesses, &key, witness_txid.to_string().into_bytes())?;
+        for input in transition.inputs.iter() {
+            let prev_out = input.prev_out;
+            if let Some((outpoint, data)) = self.spent(contract_id, prev_out)? {
+                self.derive(contract_id, prev_out.ty, outpoint, &data, false)?;
+            }
+        }
+        self.derive_assigned(contract_id, transition, Some(witness_txid))
+    }
+
+    fn transitions(&self, contract_id: ContractId) -> Result<Vec<Transition>, StashError> {
+        self.scan(Table::Transitions, &format!("{contract_id}:"))?
+            .into_iter()
+            .map(|(_, bytes)| decode(Table::Transitions, bytes))
+            .collect()
+    }
+
+    /// Witness transaction of a stored transition.
+    fn witness(&self, contract_id: ContractId, opid: OpId) -> Result<Txid, StashError> {
+        let key = format!("{contract_id}:{opid}");
+        let bytes =
+            self.get(Table::Witnesses, &key)?.ok_or(StashError::NotFound(Table::Witnesses, key))?;
+        String::from_utf8(bytes)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or(StashError::Corrupted(Table::Witnesses))
+    }
+
+    fn put_anchor(
+        &mut self,
+        witness_txid: Txid,
+        anchor: &Anchor<MerkleBlock>,
+    ) -> Result<(), StashError> {
+        self.put(Table::Anchors, &witness_txid.to_string(), encode(anchor))
+    }
+
+    fn anchor(&self, witness_txid: Txid) -> Result<Anchor<MerkleBlock>, StashError> {
+        self.fetch(Table::Anchors, witness_txid.to_string())
+    }
+
+    /// Outpoint and state of a spent assignment, or `None` if the operation
+    /// which created it is not in the stash.
+    fn spent(
+        &self,
+        contract_id: ContractId,
+        prev_out: Opout,
+    ) -> Result<Option<(Outpoint, Vec<u8>)>, StashError> {
+        let key = format!("{contract_id}:{}", prev_out.op);
+        let assigned = match self.get(Table::Transitions, &key)? {
+            Some(bytes) => {
+                let transition: Transition = decode(Table::Transitions, bytes)?;
+                let witness = self.witness(contract_id, prev_out.op)?;
+                structured(&transition, Some(witness), prev_out.ty)
+            }
+            None => match self.genesis(contract_id) {
+                Ok(genesis) if genesis.id() == prev_out.op => {
+                    structured(&genesis, None, prev_out.ty)
+                }
+                Ok(_) | Err(StashError::NotFound(..)) => return Ok(None),
+                Err(err) => return Err(err),
+            },
+        };
+        Ok(assigned
+            .into_iter()
+            .find(|(no, ..)| *no == prev_out.no)
+            .map(|(_, outpoint, data)| (outpoint, data)))
+    }
+
+    /// Adds the balances and token owners assigned by `op`.
+    fn derive_assigned(
+        &mut self,
+        contract_id: ContractId,
+        op: &impl Operation,
+        witness: Option<Txid>,
+    ) -> Result<(), StashError> {
+        for ty in DERIVED_TYPES {
+            for (_, outpoint, data) in structured(op, witness, ty) {
+                self.derive(contract_id, ty, outpoint, &data, true)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies an assigned (`assigned`) or spent state to the derived tables.
+    /// RGB-E20 assets change the seal balance; RGB-E721 tokens change their
+    /// owner. Other state is not derived.
+    fn derive(
+        &mut self,
+        contract_id: ContractId,
+        ty: AssignmentType,
+        seal: Outpoint,
+        data: &[u8],
+        assigned: bool,
+    ) -> Result<(), StashError> {
+        if ty == OS_ASSETS {
+            let amount = <[u8; 32]>::try_from(data)
+                .map(Amount256::from)
+                .map_err(|_| StashError::Corrupted(Table::Balances))?;
+            let balance = self.balance(contract_id, seal)?;
+            let balance = if assigned {
+                balance.checked_add(amount)
+            } else {
+                balance.checked_sub(amount)
+            };
+            let balance = balance.map_err(|_| StashError::Corrupted(Table::Balances))?;
+            return self.set_balance(contract_id, seal, balance);
+        }
+        if ty == OS_ASSET || ty == OS_LOCKED_ASSET {
+            // allocations start with the little-endian token index
+            let token_id = data
+                .get(..4)
+                .and_then(|index| <[u8; 4]>::try_from(index).ok())
+                .map(u32::from_le_bytes)
+                .ok_or(StashError::Corrupted(Table::TokenOwners))?;
+            if assigned {
+                return self.set_token_owner(contract_id, token_id, seal);
+            }
+            if self.token_owner(contract_id, token_id)? == Some(seal) {
+                let key = format!("{contract_id}:{token_id:010}");
+                return self.delete(Table::TokenOwners, &key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the RGB-E20 balance of a seal; zero removes the record.
+    fn set_balance(
+        &mut self,
+        contract_id: ContractId,
+        seal: Outpoint,
+        amount: Amount256,
+    ) -> Result<(), StashError> {
+        let key = format!("{contract_id}:{seal}");
+        if amount == Amount256::ZERO {
+            return self.delete(Table::Balances, &key);
+        }
+        self.put(Table::Balances, &key, amount.to_vec())
+    }
+
+    fn balance(&self, contract_id: ContractId, seal: Outpoint) -> Result<Amount256, StashError> {
+        let Some(bytes) = self.get(Table::Balances, &format!("{contract_id}:{seal}"))? else {
+            return Ok(Amount256::ZERO);
+        };
+        <[u8; 32]>::try_from(bytes)
+            .map(Amount256::from)
+            .map_err(|_| StashError::Corrupted(Table::Balances))
+    }
+
+    fn balances(
+        &self,
+        contract_id: ContractId,
+    ) -> Result<BTreeMap<Outpoint, Amount256>, StashError> {
+        let prefix = format!("{contract_id}:");
+        self.scan(Table::Balances, &prefix)?
+            .into_iter()
+            .map(|(key, bytes)| {
+                let seal = key[prefix.len()..]
+                    .parse()
+                    .map_err(|_| StashError::Corrupted(Table::Balances))?;
+                let amount = <[u8; 32]>::try_from(bytes)
+                    .map_err(|_| StashError::Corrupted(Table::Balances))?;
+                Ok((seal, Amount256::from(amount)))
+            })
+            .collect()
+    }
+
+    fn set_token_owner(
+        &mut self,
+        contract_id: ContractId,
+        token_id: u32,
+        owner: Outpoint,
+    ) -> Result<(), StashError> {
+        let key = format!("{contract_id}:{token_id:010}");
+        self.put(Table::TokenOwners, &key, owner.to_string().into_bytes())
+    }
+
+    fn token_owner(
+        &self,
+        contract_id: ContractId,
+        token_id: u32,
+    ) -> Result<Option<Outpoint>, StashError> {
+        let key = format!("{contract_id}:{token_id:010}");
+        self.get(Table::TokenOwners, &key)?
+            .map(|bytes| {
+                String::from_utf8(bytes)
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or(StashError::Corrupted(Table::TokenOwners))
+            })
+            .transpose()
+    }
+}
+
+impl<S: Stash + ?Sized> StashExt for S {}
+
+/// Schema migrations of the SQLite backend, applied in order. The number of
+/// applied ones is kept in `PRAGMA user_version`.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE schemas (key TEXT PRIMARY KEY, value BLOB NOT NULL);
+     CREATE TABLE iface_impls (key TEXT PRIMARY KEY, value BLOB NOT NULL);
+     CREATE TABLE genesis (key TEXT PRIMARY KEY, value BLOB NOT NULL);
+     CREATE TABLE transitions (key TEXT PRIMARY KEY, value BLOB NOT NULL);
+     CREATE TABLE anchors (key TEXT PRIMARY KEY, value BLOB NOT NULL);",
+    "CREATE TABLE balances (key TEXT PRIMARY KEY, value BLOB NOT NULL);
+     CREATE TABLE token_owners (key TEXT PRIMARY KEY, value BLOB NOT NULL);",
+    "CREATE TABLE witnesses (key TEXT PRIMARY KEY, value BLOB NOT NULL);",
+];
+
+/// Stash kept in a single SQLite database.
+pub struct SqliteStash(Connection);
+
+impl SqliteStash {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StashError> {
+        Self::migrate(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self, StashError> { Self::migrate(Connection::open_in_memory()?) }
+
+    fn migrate(mut conn: Connection) -> Result<Self, StashError> {
+        let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        // a newer build may have changed tables this one would misread
+        if version > MIGRATIONS.len() {
+            return Err(StashError::UnsupportedVersion(version));
+        }
+        let tx = conn.transaction()?;
+        for migration in MIGRATIONS.iter().skip(version) {
+            tx.execute_batch(migration)?;
+        }
+        tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+        tx.commit()?;
+        Ok(SqliteStash(conn))
+    }
+}
+
+impl Stash for SqliteStash {
+    fn put(&mut self, table: Table, key: &str, value: Vec<u8>) -> Result<(), StashError> {
+        let sql = format!("INSERT OR REPLACE INTO {} (key, value) VALUES (?1, ?2)", table.name());
+        self.0.execute(&sql, params![key, value])?;
+        Ok(())
+    }
+
+    fn get(&self, table: Table, key: &str) -> Result<Option<Vec<u8>>, StashError> {
+        let sql = format!("SELECT value FROM {} WHERE key = ?1", table.name());
+        Ok(self.0.query_row(&sql, params![key], |row| row.get(0)).optional()?)
+    }
+
+    fn scan(&self, table: Table, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, StashError> {
+        let sql = format!(
+            "SELECT key, value FROM {} WHERE substr(key, 1, ?2) = ?1 ORDER BY key",
+            table.name()
+        );
+        let mut stmt = self.0.prepare(&sql)?;
+        let rows = stmt.query_map(params![prefix, prefix.len()], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn delete(&mut self, table: Table, key: &str) -> Result<(), StashError> {
+        let sql = format!("DELETE FROM {} WHERE key = ?1", table.name());
+        self.0.execute(&sql, params![key])?;
+        Ok(())
+    }
+}
+
+/// Stash keeping every record in its own file, one directory per table.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FsStash(PathBuf);
+
+impl FsStash {
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, StashError> {
+        let dir = dir.into();
+        for table in Table::ALL {
+            fs::create_dir_all(dir.join(table.name()))?;
+        }
+        Ok(FsStash(dir))
+    }
+
+    // Keys contain ':' which is not allowed in Windows file names. Hex keeps
+    // key prefixes as file name prefixes, so scans still work.
+    fn path(&self, table: Table, key: &str) -> PathBuf {
+        self.0.join(table.name()).join(key.as_bytes().to_hex())
+    }
+}
+
+impl Stash for FsStash {
+    fn put(&mut self, table: Table, key: &str, value: Vec<u8>) -> Result<(), StashError> {
+        Ok(fs::write(self.path(table, key), value)?)
+    }
+
+    fn get(&self, table: Table, key: &str) -> Result<Option<Vec<u8>>, StashError> {
+        match fs::read(self.path(table, key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn scan(&self, table: Table, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, StashError> {
+        let prefix = prefix.as_bytes().to_hex();
+        let mut records = vec![];
+        for entry in fs::read_dir(self.0.join(table.name()))? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with(&prefix) {
+                continue;
+            }
+            let key = Vec::<u8>::from_hex(&name)
+                .ok()
+                .and_then(|key| String::from_utf8(key).ok())
+                .ok_or(StashError::Corrupted(table))?;
+            records.push((key, fs::read(entry.path())?));
+        }
+        records.sort();
+        Ok(records)
+    }
+
+    fn delete(&mut self, table: Table, key: &str) -> Result<(), StashError> {
+        match fs::remove_file(self.path(table, key)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::{SmallBlob, U16};
+    use amplify::Wrapper;
+    use bp::Chain;
+    use rgbstd::contract::{GenesisSeal, GraphSeal};
+    use rgbstd::stl::{Allocation, OwnedFraction};
+    use rgbstd::{Assign, Assignments, Input, Inputs, RevealedData};
+
+    use super::*;
+
+    fn seal(n: u8) -> Outpoint { Outpoint::new([n; 32].into(), 0) }
+
+    fn data(bytes: Vec<u8>) -> RevealedData {
+        RevealedData::from(SmallBlob::try_from(bytes).unwrap())
+    }
+
+    fn amount(value: u64) -> RevealedData { data(Amount256::from(value).to_vec()) }
+
+    fn token(index: u32) -> RevealedData {
+        let allocation = Allocation::with(index.into(), OwnedFraction::from(1));
+        data(allocation.to_strict_serialized::<U16>().unwrap().into_inner())
+    }
+
+    /// Genesis assigning 10 and 5 tokens to seals 1 and 2 and token 7 to
+    /// seal 3.
+    fn genesis() -> Genesis {
+        let genesis_seal = |n| GenesisSeal::from(seal(n));
+        Genesis {
+            ffv: zero!(),
+            schema_id: SchemaId::from([0x5c; 32]),
+            chain: Chain::Regtest,
+            metadata: none!(),
+            globals: none!(),
+            assignments: Assignments::from_inner(confined_bmap! {
+                OS_ASSETS => TypedAssigns::Structured(confined_vec![
+                    Assign::revealed(genesis_seal(1), amount(10)),
+                    Assign::revealed(genesis_seal(2), amount(5)),
+                ]),
+                OS_ASSET => TypedAssigns::Structured(confined_vec![
+                    Assign::revealed(genesis_seal(3), token(7)),
+                ]),
+            }),
+            valencies: none!(),
+        }
+    }
+
+    /// Transfer of the genesis tokens on seal 1 and token 7 to seals 4, 5
+    /// and 6.
+    fn transfer(genesis: &Genesis) -> Transition {
+        let graph_seal = |n| GraphSeal::from(seal(n));
+        let input = |ty| Input::with(Opout::new(genesis.id(), ty, 0));
+        Transition {
+            ffv: zero!(),
+            contract_id: genesis.contract_id(),
+            transition_type: 20000,
+            metadata: none!(),
+            globals: none!(),
+            inputs: Inputs::from_inner(confined_bset! { input(OS_ASSETS), input(OS_ASSET) }),
+            assignments: Assignments::from_inner(confined_bmap! {
+                OS_ASSETS => TypedAssigns::Structured(confined_vec![
+                    Assign::revealed(graph_seal(4), amount(6)),
+                    Assign::revealed(graph_seal(5), amount(4)),
+                ]),
+                OS_ASSET => TypedAssigns::Structured(confined_vec![
+                    Assign::revealed(graph_seal(6), token(7)),
+                ]),
+            }),
+            valencies: none!(),
+        }
+    }
+
+    fn derived_history(stash: &mut dyn Stash) {
+        let genesis = genesis();
+        let contract_id = genesis.contract_id();
+        for _ in 0..2 {
+            stash.put_genesis(&genesis).unwrap();
+            assert_eq!(stash.balances(contract_id).unwrap(), bmap! {
+                seal(1) => Amount256::from(10u64),
+                seal(2) => Amount256::from(5u64),
+            });
+            assert_eq!(stash.token_owner(contract_id, 7).unwrap(), Some(seal(3)));
+        }
+
+        let transfer = transfer(&genesis);
+        let witness_txid: Txid = [0x77; 32].into();
+        for _ in 0..2 {
+            stash.put_transition(contract_id, &transfer, witness_txid).unwrap();
+            assert_eq!(stash.balances(contract_id).unwrap(), bmap! {
+                seal(2) => Amount256::from(5u64),
+                seal(4) => Amount256::from(6u64),
+                seal(5) => Amount256::from(4u64),
+            });
+            assert_eq!(stash.token_owner(contract_id, 7).unwrap(), Some(seal(6)));
+        }
+        assert_eq!(stash.witness(contract_id, transfer.id()).unwrap(), witness_txid);
+        assert_eq!(stash.transitions(contract_id).unwrap(), vec![transfer]);
+        assert_eq!(stash.genesis(contract_id).unwrap(), genesis);
+    }
+
+    // takes `dyn Stash` to keep the trait object safe
+    fn derived_tables(stash: &mut dyn Stash) {
+        let contract_id = ContractId::from([0xc0; 32]);
+        let seal = Outpoint::new([0x01; 32].into(), 1);
+        assert_eq!(stash.balance(contract_id, seal).unwrap(), Amount256::ZERO);
+        stash.set_balance(contract_id, seal, 5u64.into()).unwrap();
+        assert_eq!(stash.balances(contract_id).unwrap(), bmap! { seal => Amount256::from(5u64) });
+        stash.set_balance(contract_id, seal, Amount256::ZERO).unwrap();
+        assert!(stash.balances(contract_id).unwrap().is_empty());
+
+        stash.set_token_owner(contract_id, 7, seal).unwrap();
+        assert_eq!(stash.token_owner(contract_id, 7).unwrap(), Some(seal));
+        assert_eq!(stash.token_owner(contract_id, 8).unwrap(), None);
+        assert!(matches!(
+            stash.genesis(contract_id),
+            Err(StashError::NotFound(Table::Genesis, _))
+        ));
+    }
+
+    #[test]
+    fn sqlite() {
+        derived_tables(&mut SqliteStash::in_memory().unwrap());
+        derived_history(&mut SqliteStash::in_memory().unwrap());
+    }
+
+    #[test]
+    fn fs() {
+        let dir = std::env::temp_dir().join(format!("rgb-stash-{}", std::process::id()));
+        derived_tables(&mut FsStash::open(&dir).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+        derived_history(&mut FsStash::open(&dir).unwrap());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn migrations() {
+        let path = std::env::temp_dir().join(format!("rgb-stash-{}.db", std::process::id()));
+        let contract_id = ContractId::from([0xc0; 32]);
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        conn.execute("INSERT INTO genesis (key, value) VALUES (?1, x'00')", params![
+            contract_id.to_string()
+        ])
+        .unwrap();
+        drop(conn);
+
+        // later migrations keep existing records and add their tables
+        let mut stash = SqliteStash::open(&path).unwrap();
+        assert_eq!(stash.get(Table::Genesis, &contract_id.to_string()).unwrap(), Some(vec![0]));
+        stash.set_balance(contract_id, seal(1), 5u64.into()).unwrap();
+        stash.put(Table::Witnesses, "key", vec![1]).unwrap();
+        let version: usize =
+            stash.0.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+        drop(stash);
+
+        // reopening applies nothing twice
+        let stash = SqliteStash::open(&path).unwrap();
+        assert_eq!(stash.balance(contract_id, seal(1)).unwrap(), Amount256::from(5u64));
+        stash.0.pragma_update(None, "user_version", MIGRATIONS.len() + 1).unwrap();
+        drop(stash);
+
+        assert!(matches!(
+            SqliteStash::open(&path),
+            Err(StashError::UnsupportedVersion(version)) if version == MIGRATIONS.len() + 1
+        ));
+        fs::remove_file(path).unwrap();
+    }
+}
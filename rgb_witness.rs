@@ -0,0 +1,299 @@
+//Reorg-aware witness status
+use std::collections::BTreeMap;
+
+use bitcoin::Txid;
+use bp::Outpoint;
+
+use crate::rgb_e20_interface::{checked_sum, Amount256, Error};
+use crate::rgb_resolver::{is_not_found, Resolver, ResolverError};
+
+/// Mining status of the witness transaction behind an allocation.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+pub enum WitnessStatus {
+    /// Mined at `height`; `depth` is 1 for the chain tip.
+    #[display("mined at {height} ({depth} confirmations)")]
+    Mined { height: u32, depth: u32 },
+    /// Known to the resolver but not mined yet.
+    #[display("tentative")]
+    Tentative,
+    /// Neither mined nor known to the resolver any more, e.g. reorged out and
+    /// double-spent. Its allocations no longer exist.
+    #[display("archived")]
+    Archived,
+}
+
+impl WitnessStatus {
+    pub fn depth(self) -> u32 {
+        match self {
+            WitnessStatus::Mined { depth, .. } => depth,
+            WitnessStatus::Tentative | WitnessStatus::Archived => 0,
+        }
+    }
+
+    /// Whether allocations behind this witness count for a query requiring
+    /// `min_depth` confirmations. Zero includes tentative witnesses.
+    pub fn satisfies(self, min_depth: u32) -> bool {
+        match self {
+            WitnessStatus::Archived => false,
+            status => status.depth() >= min_depth,
+        }
+    }
+}
+
+/// Witness status change reported by [`WitnessTracker::refresh`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct StatusChange {
+    pub txid: Txid,
+    pub from: WitnessStatus,
+    pub to: WitnessStatus,
+}
+
+impl StatusChange {
+    /// The witness left the block it was mined in.
+    pub fn is_reorg(&self) -> bool {
+        match (self.from, self.to) {
+            (WitnessStatus::Mined { height: old, .. }, WitnessStatus::Mined { height: new, .. }) => {
+                old != new
+            }
+            (WitnessStatus::Mined { .. }, _) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Tracks the status of witness transactions, recomputing all of them when
+/// the resolver tip moves.
+pub struct WitnessTracker<R: Resolver> {
+    resolver: R,
+    tip: u32,
+    statuses: BTreeMap<Txid, WitnessStatus>,
+}
+
+impl<R: Resolver> WitnessTracker<R> {
+    /// Creates a tracker starting at the current resolver tip.
+    pub fn with(resolver: R) -> Result<Self, ResolverError> {
+        let tip = resolver.tip_height()?;
+        Ok(WitnessTracker {
+            resolver,
+            tip,
+            statuses: none!(),
+        })
+    }
+
+    pub fn tip(&self) -> u32 { self.tip }
+
+    /// Status of a witness, or `None` if it is not tracked. Genesis
+    /// allocations have no witness and are always considered mined at depth
+    /// [`u32::MAX`].
+    pub fn status(&self, witness: Option<Txid>) -> Option<WitnessStatus> {
+        match witness {
+            None => Some(WitnessStatus::Mined {
+                height: 0,
+                depth: u32::MAX,
+            }),
+            Some(txid) => self.statuses.get(&txid).copied(),
+        }
+    }
+
+    fn resolve(&self, txid: Txid) -> Result<WitnessStatus, ResolverError> {
+        match self.resolver.resolve_witness(txid) {
+            Ok(witness) => Ok(match witness.height {
+                Some(height) if height <= self.tip => WitnessStatus::Mined {
+                    height,
+                    depth: self.tip - height + 1,
+                },
+                _ => WitnessStatus::Tentative,
+            }),
+            Err(ResolverError::Unknown(_)) => Ok(WitnessStatus::Archived),
+            Err(ResolverError::Electrum(err)) if is_not_found(&err) => Ok(WitnessStatus::Archived),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Starts tracking a witness and returns its current status.
+    pub fn track(&mut self, txid: Txid) -> Result<WitnessStatus, ResolverError> {
+        let status = self.resolve(txid)?;
+        self.statuses.insert(txid, status);
+        Ok(status)
+    }
+
+    /// Re-queries the tip and every tracked witness, returning the witnesses
+    /// whose status changed. A tip which went down or any change reported as
+    /// [`StatusChange::is_reorg`] means balances must be recomputed.
+    pub fn refresh(&mut self) -> Result<Vec<StatusChange>, ResolverError> {
+        self.tip = self.resolver.tip_height()?;
+        let mut changes = vec![];
+        let txids = self.statuses.keys().copied().collect::<Vec<_>>();
+        for txid in txids {
+            let to = self.resolve(txid)?;
+            let from = self.statuses.insert(txid, to).expect("tracked witness");
+            let moved = match (from, to) {
+                (WitnessStatus::Mined { height: a, .. }, WitnessStatus::Mined { height: b, .. }) => {
+                    a != b
+                }
+                _ => from != to,
+            };
+            if moved {
+                changes.push(StatusChange { txid, from, to });
+            }
+        }
+        Ok(changes)
+    }
+}
+
+/// Allocation together with the witness that created it and the one
+/// spending it, if any.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct WitnessedAllocation<T> {
+    pub outpoint: Outpoint,
+    /// `None` for genesis allocations.
+    pub witness: Option<Txid>,
+    /// Witness of the transition spending the allocation, `None` while it is
+    /// unspent.
+    pub spent_by: Option<Txid>,
+    pub state: T,
+}
+
+/// Allocations whose witness has at least `min_depth` confirmations and
+/// which are not spent by a witness with that many. Allocations with an
+/// untracked witness are left out; ones spent by an untracked or archived
+/// witness still count.
+pub fn confirmed<'a, T, R: Resolver>(
+    allocations: &'a [WitnessedAllocation<T>],
+    tracker: &'a WitnessTracker<R>,
+    min_depth: u32,
+) -> impl Iterator<Item = &'a WitnessedAllocation<T>> + 'a {
+    allocations
+        .iter()
+        .filter(move |allocation| {
+            tracker
+                .status(allocation.witness)
+                .is_some_and(|status| status.satisfies(min_depth))
+        })
+        .filter(move |allocation| {
+            !allocation
+                .spent_by
+                .and_then(|txid| tracker.status(Some(txid)))
+                .is_some_and(|status| status.satisfies(min_depth))
+        })
+}
+
+/// `balance_of` an account from its RGB-E20 allocations, counting only
+/// witnesses with at least `min_depth` confirmations.
+pub fn balance_of<R: Resolver>(
+    allocations: &[WitnessedAllocation<Amount256>],
+    tracker: &WitnessTracker<R>,
+    min_depth: u32,
+) -> Result<Amount256, Error> {
+    checked_sum(confirmed(allocations, tracker, min_depth).map(|allocation| allocation.state))
+}
+
+/// `owner_of` a token from the RGB-E721 allocations of all its past owners:
+/// the most recently mined allocation with at least `min_depth`
+/// confirmations, or the tentative one when `min_depth` is zero.
+pub fn owner_of<'a, T, R: Resolver>(
+    allocations: &'a [WitnessedAllocation<T>],
+    tracker: &'a WitnessTracker<R>,
+    min_depth: u32,
+) -> Option<&'a WitnessedAllocation<T>> {
+    confirmed(allocations, tracker, min_depth)
+        .min_by_key(|allocation| tracker.status(allocation.witness).map_or(0, WitnessStatus::depth))
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::absolute::LockTime;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut};
+
+    use super::*;
+    use crate::rgb_resolver::FileResolver;
+
+    fn tx(n: u8) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::from_byte_array([n; 32]), 0),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: bitcoin::Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 1_000,
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    fn allocation(
+        n: u8,
+        witness: Option<u8>,
+        spent_by: Option<u8>,
+        amount: u64,
+    ) -> WitnessedAllocation<Amount256> {
+        WitnessedAllocation {
+            outpoint: format!("{}:0", format!("{n:02x}").repeat(32)).parse().unwrap(),
+            witness: witness.map(|n| tx(n).txid()),
+            spent_by: spent_by.map(|n| tx(n).txid()),
+            state: Amount256::from(amount),
+        }
+    }
+
+    /// `tx(1)` mined at 100 and `tx(2)` pending, with the tip at 102.
+    fn tracker() -> WitnessTracker<FileResolver> {
+        let mut resolver = FileResolver::default();
+        resolver.add(tx(1), Some(100), Some(1_700_000_000));
+        resolver.add(tx(2), None, None);
+        resolver.set_tip(102);
+        let mut tracker = WitnessTracker::with(resolver).unwrap();
+        for n in 1..=3 {
+            tracker.track(tx(n).txid()).unwrap();
+        }
+        tracker
+    }
+
+    #[test]
+    fn statuses() {
+        let tracker = tracker();
+        assert_eq!(tracker.tip(), 102);
+        let status = |n: u8| tracker.status(Some(tx(n).txid()));
+        assert_eq!(status(1), Some(WitnessStatus::Mined { height: 100, depth: 3 }));
+        assert_eq!(status(2), Some(WitnessStatus::Tentative));
+        assert_eq!(status(3), Some(WitnessStatus::Archived));
+        assert_eq!(status(4), None);
+        assert_eq!(tracker.status(None).map(WitnessStatus::depth), Some(u32::MAX));
+    }
+
+    #[test]
+    fn spent_allocations() {
+        let tracker = tracker();
+        let allocations = [
+            allocation(1, None, None, 10),
+            allocation(2, Some(1), None, 5),
+            allocation(3, Some(2), None, 7),
+            allocation(4, Some(1), Some(2), 3),
+            allocation(5, Some(1), Some(3), 2),
+            allocation(6, Some(4), None, 100),
+        ];
+        let balance = |min_depth| balance_of(&allocations, &tracker, min_depth).unwrap();
+        // the pending spend of allocation 4 already counts without confirmations
+        assert_eq!(balance(0), Amount256::from(24u64));
+        assert_eq!(balance(1), Amount256::from(20u64));
+        assert_eq!(balance(4), Amount256::from(10u64));
+    }
+
+    #[test]
+    fn reorg() {
+        let mut tracker = tracker();
+        tracker.resolver.add(tx(1), Some(103), None);
+        tracker.resolver.add(tx(2), Some(103), None);
+        let changes = tracker.refresh().unwrap();
+        assert_eq!(tracker.tip(), 103);
+        assert_eq!(changes.len(), 2);
+        let change = |n: u8| changes.iter().find(|change| change.txid == tx(n).txid()).unwrap();
+        assert!(change(1).is_reorg());
+        assert_eq!(change(2).to, WitnessStatus::Mined { height: 103, depth: 1 });
+        assert!(!change(2).is_reorg());
+    }
+}